@@ -0,0 +1,95 @@
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr};
+
+/// Implements `irsdk::Telemetry` for a struct with named fields.
+///
+/// Fields read the var named after them in CamelCase; `#[irsdk(name = "...")]`
+/// overrides the name.
+#[proc_macro_derive(Telemetry, attributes(irsdk))]
+pub fn derive_telemetry(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&input, "Telemetry needs a struct with named fields")),
+        },
+        _ => return Err(Error::new_spanned(&input, "Telemetry can only be derived for structs")),
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(&input.generics, "Telemetry structs cannot be generic"));
+    }
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let handles = format_ident!("__{}TelemetryHandles", ident);
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    let mut names = Vec::new();
+    for field in fields {
+        let field_ident = field.ident.clone().unwrap();
+        let mut name = None;
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("irsdk")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `name = \"...\"`"))
+                }
+            })?;
+        }
+        let name = name.unwrap_or_else(|| {
+            let field_name = field_ident.to_string();
+            LitStr::new(&camel_case(field_name.trim_start_matches("r#")), Span::call_site())
+        });
+        idents.push(field_ident);
+        types.push(&field.ty);
+        names.push(name);
+    }
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[derive(Clone)]
+        #[allow(non_camel_case_types)]
+        #vis struct #handles {
+            #(#idents: <#types as ::irsdk::TelemetryField>::Handle,)*
+        }
+
+        impl ::irsdk::Telemetry for #ident {
+            type Handles = #handles;
+
+            fn bind(
+                var_headers: &::std::collections::HashMap<::std::string::String, ::irsdk::VarHeader<'static>>,
+            ) -> ::std::result::Result<Self::Handles, ::irsdk::VarError> {
+                ::std::result::Result::Ok(#handles {
+                    #(#idents: ::irsdk::telemetry::bind_field::<#types>(var_headers, #names)?,)*
+                })
+            }
+
+            fn read(handles: &Self::Handles, data: &[u8]) -> ::std::result::Result<Self, ::irsdk::VarError> {
+                ::std::result::Result::Ok(#ident {
+                    #(#idents: ::irsdk::telemetry::read_field::<#types>(&handles.#idents, data, #names)?,)*
+                })
+            }
+        }
+    })
+}
+
+// `lap_dist_pct` -> `LapDistPct`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,34 @@
+use std::env;
+use std::fs;
+use std::process;
+use irsdk::codegen;
+
+const USAGE: &str = "usage: irsdk-codegen <file.ibt | dump.bin> [StructName] [out.rs]";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() || args.len() > 3 {
+        eprintln!("{}", USAGE);
+        process::exit(2);
+    }
+    let struct_name = args.get(1).map_or("Vars", String::as_str);
+
+    let mem = fs::read(&args[0]).unwrap_or_else(|e| {
+        eprintln!("failed to read {}: {}", args[0], e);
+        process::exit(1);
+    });
+    let module = codegen::generate_from_bytes(&mem, struct_name).unwrap_or_else(|e| {
+        eprintln!("failed to parse {}: {}", args[0], e);
+        process::exit(1);
+    });
+
+    match args.get(2) {
+        Some(out) => {
+            if let Err(e) = fs::write(out, module) {
+                eprintln!("failed to write {}: {}", out, e);
+                process::exit(1);
+            }
+        }
+        None => print!("{}", module),
+    }
+}
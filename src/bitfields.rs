@@ -0,0 +1,134 @@
+use std::fmt;
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+use crate::constants::{camera_state, engine_warnings, flags, pace_flags, pit_sv_flags};
+use crate::handle::VarScalar;
+use crate::value::{FromVarValue, VarType, VarValue};
+
+bitflags! {
+    /// `SessionFlags` and `CarIdxSessionFlags`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct SessionFlags: u32 {
+        const CHECKERED = flags::CHECKERED;
+        const WHITE = flags::WHITE;
+        const GREEN = flags::GREEN;
+        const YELLOW = flags::YELLOW;
+        const RED = flags::RED;
+        const BLUE = flags::BLUE;
+        const DEBRIS = flags::DEBRIS;
+        const CROSSED = flags::CROSSED;
+        const YELLOW_WAVING = flags::YELLOW_WAVING;
+        const ONE_LAP_TO_GREEN = flags::ONE_LAP_TO_GREEN;
+        const GREEN_HELD = flags::GREEN_HELD;
+        const TEN_TO_GO = flags::TEN_TO_GO;
+        const FIVE_TO_GO = flags::FIVE_TO_GO;
+        const RANDOM_WAVING = flags::RANDOM_WAVING;
+        const CAUTION = flags::CAUTION;
+        const CAUTION_WAVING = flags::CAUTION_WAVING;
+        const BLACK = flags::BLACK;
+        const DISQUALIFY = flags::DISQUALIFY;
+        const SERVICIBLE = flags::SERVICIBLE;
+        const FURLED = flags::FURLED;
+        const REPAIR = flags::REPAIR;
+        const START_HIDDEN = flags::START_HIDDEN;
+        const START_READY = flags::START_READY;
+        const START_SET = flags::START_SET;
+        const START_GO = flags::START_GO;
+    }
+}
+
+bitflags! {
+    /// `EngineWarnings`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct EngineWarnings: u32 {
+        const WATER_TEMP_WARNING = engine_warnings::WATER_TEMP_WARNING;
+        const FUEL_PRESSURE_WARNING = engine_warnings::FUEL_PRESSURE_WARNING;
+        const OIL_PRESSURE_WARNING = engine_warnings::OIL_PRESSURE_WARNING;
+        const ENGINE_STALLED = engine_warnings::ENGINE_STALLED;
+        const PIT_SPEED_LIMITER = engine_warnings::PIT_SPEED_LIMITER;
+        const REV_LIMITER_ACTIVE = engine_warnings::REV_LIMITER_ACTIVE;
+        const OIL_TEMP_WARNING = engine_warnings::OIL_TEMP_WARNING;
+    }
+}
+
+bitflags! {
+    /// `CamCameraState`; pass `.bits()` to `IRSDK::cam_set_state`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct CameraState: u32 {
+        const IS_SESSION_SCREEN = camera_state::IS_SESSION_SCREEN;
+        const IS_SCENIC_ACTIVE = camera_state::IS_SCENIC_ACTIVE;
+        const CAM_TOOL_ACTIVE = camera_state::CAM_TOOL_ACTIVE;
+        const UI_HIDDEN = camera_state::UI_HIDDEN;
+        const USE_AUTO_SHOT_SELECTION = camera_state::USE_AUTO_SHOT_SELECTION;
+        const USE_TEMPORARY_EDITS = camera_state::USE_TEMPORARY_EDITS;
+        const USE_KEY_ACCELERATION = camera_state::USE_KEY_ACCELERATION;
+        const USE_KEY10X_ACCELERATION = camera_state::USE_KEY10X_ACCELERATION;
+        const USE_MOUSE_AIM_MODE = camera_state::USE_MOUSE_AIM_MODE;
+    }
+}
+
+bitflags! {
+    /// `PitSvFlags`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct PitSvFlags: u32 {
+        const LF_TIRE_CHANGE = pit_sv_flags::LF_TIRE_CHANGE;
+        const RF_TIRE_CHANGE = pit_sv_flags::RF_TIRE_CHANGE;
+        const LR_TIRE_CHANGE = pit_sv_flags::LR_TIRE_CHANGE;
+        const RR_TIRE_CHANGE = pit_sv_flags::RR_TIRE_CHANGE;
+        const FUEL_FILL = pit_sv_flags::FUEL_FILL;
+        const WINDSHIELD_TEAROFF = pit_sv_flags::WINDSHIELD_TEAROFF;
+        const FAST_REPAIR = pit_sv_flags::FAST_REPAIR;
+    }
+}
+
+bitflags! {
+    /// `PaceFlags` and `CarIdxPaceFlags`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct PaceFlags: u32 {
+        const END_OF_LINE = pace_flags::END_OF_LINE;
+        const FREE_PASS = pace_flags::FREE_PASS;
+        const WAVED_AROUND = pace_flags::WAVED_AROUND;
+    }
+}
+
+// Display as `GREEN | BLUE`, with unknown bits as trailing hex. Read from
+// `BitField` vars, unknown bits are kept.
+macro_rules! impl_bitfield {
+    ($ty:ty) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                bitflags::parser::to_writer(self, f)
+            }
+        }
+
+        impl FromVarValue for $ty {
+            const TYPE_NAME: &'static str = "u32";
+
+            fn from_var_value(value: VarValue) -> Option<Self> {
+                match value {
+                    VarValue::BitField(v) => Some(<$ty>::from_bits_retain(v)),
+                    _ => None,
+                }
+            }
+        }
+
+        impl VarScalar for $ty {
+            const VAR_TYPE: VarType = VarType::BitField;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                <$ty>::from_bits_retain(u32::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    };
+}
+
+impl_bitfield!(SessionFlags);
+impl_bitfield!(EngineWarnings);
+impl_bitfield!(CameraState);
+impl_bitfield!(PitSvFlags);
+impl_bitfield!(PaceFlags);
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+use crate::structs::{Header, ParseError, VarHeader};
+use crate::value::VarType;
+
+// Units that mark enum and bitfield vars, with the var type the typed
+// wrapper reads from.
+const TYPED_UNITS: &[(&str, VarType, &str)] = &[
+    ("irsdk_Flags", VarType::BitField, "irsdk::SessionFlags"),
+    ("irsdk_EngineWarnings", VarType::BitField, "irsdk::EngineWarnings"),
+    ("irsdk_CameraState", VarType::BitField, "irsdk::CameraState"),
+    ("irsdk_PitSvFlags", VarType::BitField, "irsdk::PitSvFlags"),
+    ("irsdk_PaceFlags", VarType::BitField, "irsdk::PaceFlags"),
+    ("irsdk_TrkLoc", VarType::Int, "irsdk::TrkLoc"),
+    ("irsdk_TrkSurf", VarType::Int, "irsdk::TrkSurf"),
+    ("irsdk_SessionState", VarType::Int, "irsdk::SessionState"),
+    ("irsdk_PitSvStatus", VarType::Int, "irsdk::PitSvStatus"),
+    ("irsdk_PaceMode", VarType::Int, "irsdk::PaceMode"),
+    ("irsdk_CarLeftRight", VarType::Int, "irsdk::CarLeftRight"),
+    ("irsdk_TrackWetness", VarType::Int, "irsdk::TrackWetness"),
+];
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+    "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "super", "trait",
+    "true", "type", "unsafe", "use", "where", "while", "async", "await", "dyn", "abstract", "become", "box", "do",
+    "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+/// Reads the var header table from an `.ibt` file or memory dump and
+/// generates a module for it; see `generate`.
+pub fn generate_from_bytes(mem: &[u8], struct_name: &str) -> Result<String, ParseError> {
+    let header = Header::parse(mem)?;
+    let var_headers: Vec<_> = header.var_headers().collect();
+    Ok(generate(&var_headers, struct_name))
+}
+
+/// Generates a Rust module with one `#[derive(Telemetry)]` struct holding
+/// every var, documented with its description and unit.
+pub fn generate(var_headers: &[VarHeader<'_>], struct_name: &str) -> String {
+    let mut out = String::new();
+    out.push_str("// Generated by irsdk-codegen. Do not edit.\n\n");
+    let _ = writeln!(out, "#[derive(Debug, Clone, irsdk::Telemetry)]");
+    let _ = writeln!(out, "pub struct {} {{", struct_name);
+    let mut fields = HashSet::new();
+    for var_header in var_headers {
+        let Some(field_type) = field_type(var_header) else {
+            continue;
+        };
+        let mut field = field_name(&var_header.name);
+        while !fields.insert(field.clone()) {
+            field.push('_');
+        }
+        let mut doc = var_header.desc.trim().replace(['\r', '\n'], " ");
+        let unit = var_header.unit.trim();
+        if !unit.is_empty() {
+            doc = format!("{} (`{}`)", doc, unit).trim_start().to_string();
+        }
+        if !doc.is_empty() {
+            let _ = writeln!(out, "    /// {}", doc);
+        }
+        let _ = writeln!(out, "    #[irsdk(name = {:?})]", var_header.name);
+        let _ = writeln!(out, "    pub {}: {},", field, field_type);
+    }
+    out.push_str("}\n");
+    out
+}
+
+// `None` for var types this crate cannot read.
+fn field_type(var_header: &VarHeader<'_>) -> Option<String> {
+    let var_type = VarType::try_from(var_header.var_type).ok()?;
+    if var_header.count == 1 && var_header.is_time() && matches!(var_type, VarType::Float | VarType::Double) {
+        return Some("irsdk::SessionTime".to_string());
+    }
+    let scalar = TYPED_UNITS
+        .iter()
+        .find(|(unit, typed_var_type, _)| *unit == var_header.unit.trim() && *typed_var_type == var_type)
+        .map_or(var_type.name(), |(_, _, rust_type)| rust_type);
+    Some(if var_header.count > 1 { format!("Vec<{}>", scalar) } else { scalar.to_string() })
+}
+
+// `CarIdxLapDistPct` -> `car_idx_lap_dist_pct`, `RPM` -> `rpm`, and corner
+// prefixes split off: `LFshockDefl` -> `lf_shock_defl`.
+fn field_name(name: &str) -> String {
+    let mut chars: Vec<char> = name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+    let mut field = String::new();
+    let corner = ["LF", "RF", "LR", "RR", "CF", "CR"].iter().any(|corner| name.starts_with(corner));
+    if corner && chars.get(2).is_some_and(|c| c.is_ascii_lowercase()) {
+        field.push_str(&name[..2].to_ascii_lowercase());
+        field.push('_');
+        chars.drain(..2);
+    }
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase() || prev.is_ascii_digit() || (prev.is_ascii_uppercase() && next_lower) {
+                field.push('_');
+            }
+        }
+        field.push(c.to_ascii_lowercase());
+    }
+    if field.is_empty() || field.starts_with(|c: char| c.is_ascii_digit()) {
+        field.insert(0, '_');
+    }
+    if KEYWORDS.contains(&field.as_str()) || field == "self" {
+        field.push('_');
+    }
+    field
+}
@@ -0,0 +1,31 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ConnectionState {
+    /// `startup` has not been called, or `shutdown` was called.
+    Disconnected,
+    /// Started, but the sim is not running or has exited; startup is retried.
+    WaitingForSim,
+    /// The sim is publishing new ticks.
+    Connected,
+    /// Still mapped, but no new tick arrived within the stale timeout.
+    Stale,
+}
+
+impl fmt::Display for ConnectionState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConnectionState::Disconnected => "disconnected",
+            ConnectionState::WaitingForSim => "waiting for sim",
+            ConnectionState::Connected => "connected",
+            ConnectionState::Stale => "stale",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionEvent {
+    pub from: ConnectionState,
+    pub to: ConnectionState,
+}
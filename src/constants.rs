@@ -4,6 +4,9 @@ pub const DATA_VALID_EVENT_NAME: &str = "Local\\IRSDKDataValidEvent";
 pub const MEM_MAP_FILE: &str = "Local\\IRSDKMemMapFileName";
 pub const MEM_MAP_FILE_SIZE: usize = 1164 * 1024;
 pub const BROADCAST_MSG_NAME: &str = "IRSDK_BROADCASTMSG";
+pub const HEADER_SIZE: usize = 112;
+pub const VAR_HEADER_SIZE: usize = 144;
+pub const MAX_BUFS: usize = 4;
 
 pub const VAR_TYPE_MAP: [&str; 6] = ["i8", "bool", "i32", "u32", "f32", "f64"];
 pub const YAML_CODE_PAGE: &str = "windows-1252";
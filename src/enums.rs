@@ -0,0 +1,299 @@
+use std::fmt;
+use crate::constants::{car_left_right, pace_mode, pit_sv_status, session_state, track_wetness, trk_loc, trk_surf};
+use crate::handle::VarScalar;
+use crate::value::{FromVarValue, VarType, VarValue};
+
+// Each enum converts from any i32 (values the SDK does not document land in
+// `Unknown`), so `TryFrom<i32>` comes from the blanket impl and never fails.
+macro_rules! sim_enum {
+    ($(#[$meta:meta])* $name:ident { $($variant:ident = $value:expr => $display:literal,)* }) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($variant,)*
+            Unknown(i32),
+        }
+
+        impl From<i32> for $name {
+            fn from(value: i32) -> Self {
+                match value {
+                    $(v if v == $value as i32 => $name::$variant,)*
+                    other => $name::Unknown(other),
+                }
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value as i32,)*
+                    $name::Unknown(other) => other,
+                }
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    $($name::$variant => write!(f, $display),)*
+                    $name::Unknown(other) => write!(f, "unknown ({})", other),
+                }
+            }
+        }
+
+        impl FromVarValue for $name {
+            const TYPE_NAME: &'static str = "i32";
+
+            fn from_var_value(value: VarValue) -> Option<Self> {
+                match value {
+                    VarValue::Int(v) => Some($name::from(v)),
+                    _ => None,
+                }
+            }
+        }
+
+        impl VarScalar for $name {
+            const VAR_TYPE: VarType = VarType::Int;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                $name::from(i32::from_le_bytes(bytes.try_into().unwrap()))
+            }
+        }
+    };
+}
+
+sim_enum! {
+    /// `PlayerTrackSurface` and `CarIdxTrackSurface`.
+    TrkLoc {
+        NotInWorld = trk_loc::NOT_IN_WORLD => "not in world",
+        OffTrack = trk_loc::OFF_TRACK => "off track",
+        InPitStall = trk_loc::IN_PIT_STALL => "in pit stall",
+        ApproachingPits = trk_loc::APPROACHING_PITS => "approaching pits",
+        OnTrack = trk_loc::ON_TRACK => "on track",
+    }
+}
+
+impl TrkLoc {
+    pub fn is_in_world(self) -> bool {
+        !matches!(self, TrkLoc::NotInWorld | TrkLoc::Unknown(_))
+    }
+
+    pub fn is_on_track(self) -> bool {
+        self == TrkLoc::OnTrack
+    }
+
+    pub fn is_in_pits(self) -> bool {
+        matches!(self, TrkLoc::InPitStall | TrkLoc::ApproachingPits)
+    }
+}
+
+sim_enum! {
+    /// `PlayerTrackSurfaceMaterial` and `CarIdxTrackSurfaceMaterial`.
+    TrkSurf {
+        NotInWorld = trk_surf::NOT_IN_WORLD => "not in world",
+        Undefined = trk_surf::UNDEFINED => "undefined",
+        Asphalt1 = trk_surf::ASPHALT_1 => "asphalt 1",
+        Asphalt2 = trk_surf::ASPHALT_2 => "asphalt 2",
+        Asphalt3 = trk_surf::ASPHALT_3 => "asphalt 3",
+        Asphalt4 = trk_surf::ASPHALT_4 => "asphalt 4",
+        Concrete1 = trk_surf::CONCRETE_1 => "concrete 1",
+        Concrete2 = trk_surf::CONCRETE_2 => "concrete 2",
+        RacingDirt1 = trk_surf::RACING_DIRT_1 => "racing dirt 1",
+        RacingDirt2 = trk_surf::RACING_DIRT_2 => "racing dirt 2",
+        Paint1 = trk_surf::PAINT_1 => "paint 1",
+        Paint2 = trk_surf::PAINT_2 => "paint 2",
+        Rumble1 = trk_surf::RUMBLE_1 => "rumble 1",
+        Rumble2 = trk_surf::RUMBLE_2 => "rumble 2",
+        Rumble3 = trk_surf::RUMBLE_3 => "rumble 3",
+        Rumble4 = trk_surf::RUMBLE_4 => "rumble 4",
+        Grass1 = trk_surf::GRASS_1 => "grass 1",
+        Grass2 = trk_surf::GRASS_2 => "grass 2",
+        Grass3 = trk_surf::GRASS_3 => "grass 3",
+        Grass4 = trk_surf::GRASS_4 => "grass 4",
+        Dirt1 = trk_surf::DIRT_1 => "dirt 1",
+        Dirt2 = trk_surf::DIRT_2 => "dirt 2",
+        Dirt3 = trk_surf::DIRT_3 => "dirt 3",
+        Dirt4 = trk_surf::DIRT_4 => "dirt 4",
+        Sand = trk_surf::SAND => "sand",
+        Gravel1 = trk_surf::GRAVEL_1 => "gravel 1",
+        Gravel2 = trk_surf::GRAVEL_2 => "gravel 2",
+        Grasscrete = trk_surf::GRASSCRETE => "grasscrete",
+        Astroturf = trk_surf::ASTROTURF => "astroturf",
+    }
+}
+
+impl TrkSurf {
+    pub fn is_asphalt(self) -> bool {
+        matches!(self, TrkSurf::Asphalt1 | TrkSurf::Asphalt2 | TrkSurf::Asphalt3 | TrkSurf::Asphalt4)
+    }
+
+    pub fn is_concrete(self) -> bool {
+        matches!(self, TrkSurf::Concrete1 | TrkSurf::Concrete2)
+    }
+
+    pub fn is_racing_dirt(self) -> bool {
+        matches!(self, TrkSurf::RacingDirt1 | TrkSurf::RacingDirt2)
+    }
+
+    pub fn is_paint(self) -> bool {
+        matches!(self, TrkSurf::Paint1 | TrkSurf::Paint2)
+    }
+
+    pub fn is_rumble(self) -> bool {
+        matches!(self, TrkSurf::Rumble1 | TrkSurf::Rumble2 | TrkSurf::Rumble3 | TrkSurf::Rumble4)
+    }
+
+    pub fn is_grass(self) -> bool {
+        matches!(self, TrkSurf::Grass1 | TrkSurf::Grass2 | TrkSurf::Grass3 | TrkSurf::Grass4)
+    }
+
+    pub fn is_dirt(self) -> bool {
+        matches!(self, TrkSurf::Dirt1 | TrkSurf::Dirt2 | TrkSurf::Dirt3 | TrkSurf::Dirt4)
+    }
+
+    pub fn is_gravel(self) -> bool {
+        matches!(self, TrkSurf::Gravel1 | TrkSurf::Gravel2)
+    }
+
+    /// Asphalt, concrete, racing dirt or painted lines: the surfaces a track is
+    /// meant to be driven on.
+    pub fn is_racing_surface(self) -> bool {
+        self.is_asphalt() || self.is_concrete() || self.is_racing_dirt() || self.is_paint()
+    }
+}
+
+sim_enum! {
+    /// `SessionState`.
+    SessionState {
+        Invalid = session_state::INVALID => "invalid",
+        GetInCar = session_state::GET_IN_CAR => "get in car",
+        Warmup = session_state::WARMUP => "warmup",
+        ParadeLaps = session_state::PARADE_LAPS => "parade laps",
+        Racing = session_state::RACING => "racing",
+        Checkered = session_state::CHECKERED => "checkered",
+        CoolDown = session_state::COOL_DOWN => "cool down",
+    }
+}
+
+impl SessionState {
+    pub fn is_racing(self) -> bool {
+        self == SessionState::Racing
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(self, SessionState::Checkered | SessionState::CoolDown)
+    }
+}
+
+sim_enum! {
+    /// `PlayerCarPitSvStatus`.
+    PitSvStatus {
+        None = pit_sv_status::NONE => "none",
+        InProgress = pit_sv_status::IN_PROGRESS => "in progress",
+        Complete = pit_sv_status::COMPLETE => "complete",
+        TooFarLeft = pit_sv_status::TOO_FAR_LEFT => "too far left",
+        TooFarRight = pit_sv_status::TOO_FAR_RIGHT => "too far right",
+        TooFarForward = pit_sv_status::TOO_FAR_FORWARD => "too far forward",
+        TooFarBack = pit_sv_status::TOO_FAR_BACK => "too far back",
+        BadAngle = pit_sv_status::BAD_ANGLE => "bad angle",
+        CantFixThat = pit_sv_status::CANT_FIX_THAT => "can't fix that",
+    }
+}
+
+impl PitSvStatus {
+    pub fn is_in_progress(self) -> bool {
+        self == PitSvStatus::InProgress
+    }
+
+    pub fn is_complete(self) -> bool {
+        self == PitSvStatus::Complete
+    }
+
+    /// Service was refused; the sim reports these codes from 100 up.
+    pub fn is_error(self) -> bool {
+        i32::from(self) >= pit_sv_status::TOO_FAR_LEFT as i32
+    }
+}
+
+sim_enum! {
+    /// `PaceMode`.
+    PaceMode {
+        SingleFileStart = pace_mode::SINGLE_FILE_START => "single file start",
+        DoubleFileStart = pace_mode::DOUBLE_FILE_START => "double file start",
+        SingleFileRestart = pace_mode::SINGLE_FILE_RESTART => "single file restart",
+        DoubleFileRestart = pace_mode::DOUBLE_FILE_RESTART => "double file restart",
+        NotPacing = pace_mode::NOT_PACING => "not pacing",
+    }
+}
+
+impl PaceMode {
+    pub fn is_pacing(self) -> bool {
+        !matches!(self, PaceMode::NotPacing | PaceMode::Unknown(_))
+    }
+
+    pub fn is_double_file(self) -> bool {
+        matches!(self, PaceMode::DoubleFileStart | PaceMode::DoubleFileRestart)
+    }
+
+    pub fn is_restart(self) -> bool {
+        matches!(self, PaceMode::SingleFileRestart | PaceMode::DoubleFileRestart)
+    }
+}
+
+sim_enum! {
+    /// `CarLeftRight`, the spotter state.
+    CarLeftRight {
+        Off = car_left_right::OFF => "off",
+        Clear = car_left_right::CLEAR => "clear",
+        CarLeft = car_left_right::CAR_LEFT => "car left",
+        CarRight = car_left_right::CAR_RIGHT => "car right",
+        CarLeftRight = car_left_right::CAR_LEFT_RIGHT => "cars left and right",
+        TwoCarsLeft = car_left_right::TWO_CARS_LEFT => "two cars left",
+        TwoCarsRight = car_left_right::TWO_CARS_RIGHT => "two cars right",
+    }
+}
+
+impl CarLeftRight {
+    pub fn has_car_left(self) -> bool {
+        matches!(self, CarLeftRight::CarLeft | CarLeftRight::CarLeftRight | CarLeftRight::TwoCarsLeft)
+    }
+
+    pub fn has_car_right(self) -> bool {
+        matches!(self, CarLeftRight::CarRight | CarLeftRight::CarLeftRight | CarLeftRight::TwoCarsRight)
+    }
+
+    pub fn is_clear(self) -> bool {
+        self == CarLeftRight::Clear
+    }
+}
+
+sim_enum! {
+    /// `TrackWetness`.
+    TrackWetness {
+        Unset = track_wetness::UNKNOWN => "unknown",
+        Dry = track_wetness::DRY => "dry",
+        MostlyDry = track_wetness::MOSTLY_DRY => "mostly dry",
+        VeryLightlyWet = track_wetness::VERY_LIGHTLY_WET => "very lightly wet",
+        LightlyWet = track_wetness::LIGHTLY_WET => "lightly wet",
+        ModeratelyWet = track_wetness::MODERATELY_WET => "moderately wet",
+        VeryWet = track_wetness::VERY_WET => "very wet",
+        ExtremelyWet = track_wetness::EXTREMELY_WET => "extremely wet",
+    }
+}
+
+impl TrackWetness {
+    pub fn is_dry(self) -> bool {
+        matches!(self, TrackWetness::Dry | TrackWetness::MostlyDry)
+    }
+
+    pub fn is_wet(self) -> bool {
+        matches!(
+            self,
+            TrackWetness::VeryLightlyWet
+                | TrackWetness::LightlyWet
+                | TrackWetness::ModeratelyWet
+                | TrackWetness::VeryWet
+                | TrackWetness::ExtremelyWet
+        )
+    }
+}
@@ -0,0 +1,236 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use encoding_rs::WINDOWS_1252;
+use crate::constants::*;
+use crate::irsdk::IRSDKError;
+use crate::source::TelemetrySource;
+use crate::structs::VarHeader;
+use crate::value::{VarError, VarType, VarValue};
+
+const NUM_BUF: usize = 3;
+
+fn align(offset: usize, to: usize) -> usize {
+    offset.div_ceil(to) * to
+}
+
+fn put_str(mem: &mut [u8], offset: usize, len: usize, value: &str) {
+    let (bytes, _, _) = WINDOWS_1252.encode(value);
+    let n = bytes.len().min(len - 1);
+    mem[offset..offset + n].copy_from_slice(&bytes[..n]);
+}
+
+/// An in-memory stand-in for the sim: lays out the same header, var header
+/// table, session info and rotating var buffers the sim publishes.
+pub struct FakeSim {
+    vars: Vec<VarHeader<'static>>,
+    buf_len: usize,
+    staging: Vec<u8>,
+    buffers: Vec<(i32, Vec<u8>)>,
+    session_info: Vec<u8>,
+    session_info_update: i32,
+    status: i32,
+    tick_count: i32,
+    mem: Vec<u8>,
+}
+
+impl Default for FakeSim {
+    fn default() -> Self {
+        FakeSim::new()
+    }
+}
+
+impl FakeSim {
+    pub fn new() -> Self {
+        let mut sim = FakeSim {
+            vars: Vec::new(),
+            buf_len: 0,
+            staging: Vec::new(),
+            buffers: vec![(0, Vec::new()); NUM_BUF],
+            session_info: Vec::new(),
+            session_info_update: 0,
+            status: STATUS_CONNECTED,
+            tick_count: 0,
+            mem: Vec::new(),
+        };
+        sim.layout();
+        sim
+    }
+
+    /// Declares a var. Adding vars resets every var buffer.
+    pub fn add_var(&mut self, name: &str, var_type: VarType, count: i32, unit: &str, desc: &str) -> &mut Self {
+        self.push_var(name, var_type, count, false, unit, desc)
+    }
+
+    /// Declares a var flagged `count_as_time`.
+    pub fn add_time_var(&mut self, name: &str, var_type: VarType, count: i32, unit: &str, desc: &str) -> &mut Self {
+        self.push_var(name, var_type, count, true, unit, desc)
+    }
+
+    fn push_var(&mut self, name: &str, var_type: VarType, count: i32, count_as_time: bool, unit: &str, desc: &str) -> &mut Self {
+        let offset = align(self.buf_len, var_type.size());
+        self.vars.push(VarHeader {
+            var_type: var_type as i32,
+            offset: offset as i32,
+            count,
+            count_as_time,
+            name: name.to_string().into(),
+            desc: desc.to_string().into(),
+            unit: unit.to_string().into(),
+        });
+        self.buf_len = offset + var_type.size() * count.max(0) as usize;
+        self.staging = vec![0; align(self.buf_len, 16)];
+        self.buffers = vec![(0, self.staging.clone()); NUM_BUF];
+        self.tick_count = 0;
+        self.layout();
+        self
+    }
+
+    /// Stages a value for the next `tick`.
+    pub fn set(&mut self, name: &str, value: VarValue) -> Result<(), VarError> {
+        let var_header = self
+            .vars
+            .iter()
+            .find(|vh| vh.name == name)
+            .ok_or_else(|| VarError::UnknownVar(name.to_string()))?;
+        let var_type = VarType::try_from(var_header.var_type).map_err(|_| VarError::NoData(name.to_string()))?;
+        let bytes = encode(&value);
+        let len = var_type.size() * var_header.count as usize;
+        if value.var_type() != var_type || bytes.len() > len {
+            return Err(VarError::TypeMismatch {
+                name: name.to_string(),
+                expected: var_type.name(),
+                found: value.type_name(),
+            });
+        }
+        let offset = var_header.offset as usize;
+        self.staging[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        Ok(())
+    }
+
+    /// Replaces the session info YAML, encoded as Windows-1252 like the sim
+    /// does, and bumps `session_info_update`.
+    pub fn set_session_info(&mut self, yaml: &str) {
+        self.session_info = WINDOWS_1252.encode(yaml).0.into_owned();
+        self.session_info_update += 1;
+        self.layout();
+    }
+
+    pub fn set_connected(&mut self, connected: bool) {
+        self.status = if connected { STATUS_CONNECTED } else { 0 };
+        self.layout();
+    }
+
+    /// Publishes the staged values into the next rotating buffer.
+    pub fn tick(&mut self) -> i32 {
+        self.tick_count += 1;
+        let slot = self.tick_count as usize % NUM_BUF;
+        self.buffers[slot] = (self.tick_count, self.staging.clone());
+        self.layout();
+        self.tick_count
+    }
+
+    pub fn tick_count(&self) -> i32 {
+        self.tick_count
+    }
+
+    pub fn session_info_update(&self) -> i32 {
+        self.session_info_update
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.mem
+    }
+
+    /// Writes the current layout to `path` in place, so an existing
+    /// `FileSource` mapping of the same size sees the update.
+    pub fn write_to(&self, path: &str) -> std::io::Result<()> {
+        let mut f = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+        f.write_all(&self.mem)?;
+        f.set_len(self.mem.len() as u64)
+    }
+
+    fn layout(&mut self) {
+        let var_header_offset = HEADER_SIZE;
+        let session_info_offset = align(var_header_offset + self.vars.len() * VAR_HEADER_SIZE, 16);
+        let session_info_len = align(self.session_info.len() + 1, 16);
+        let buf_len = self.staging.len();
+        let first_buf_offset = align(session_info_offset + session_info_len, 16);
+
+        let mut mem = vec![0u8; first_buf_offset + buf_len * NUM_BUF];
+        let header = [
+            2,
+            self.status,
+            60,
+            self.session_info_update,
+            session_info_len as i32,
+            session_info_offset as i32,
+            self.vars.len() as i32,
+            var_header_offset as i32,
+            NUM_BUF as i32,
+            buf_len as i32,
+        ];
+        for (i, value) in header.iter().enumerate() {
+            mem[i * 4..i * 4 + 4].copy_from_slice(&value.to_le_bytes());
+        }
+
+        for (i, (tick_count, data)) in self.buffers.iter().enumerate() {
+            let entry = 48 + i * 16;
+            let buf_offset = first_buf_offset + i * buf_len;
+            mem[entry..entry + 4].copy_from_slice(&tick_count.to_le_bytes());
+            mem[entry + 4..entry + 8].copy_from_slice(&(buf_offset as i32).to_le_bytes());
+            mem[buf_offset..buf_offset + data.len()].copy_from_slice(data);
+        }
+
+        for (i, vh) in self.vars.iter().enumerate() {
+            let offset = var_header_offset + i * VAR_HEADER_SIZE;
+            mem[offset..offset + 4].copy_from_slice(&vh.var_type.to_le_bytes());
+            mem[offset + 4..offset + 8].copy_from_slice(&vh.offset.to_le_bytes());
+            mem[offset + 8..offset + 12].copy_from_slice(&vh.count.to_le_bytes());
+            mem[offset + 12] = vh.count_as_time as u8;
+            put_str(&mut mem, offset + 16, 32, &vh.name);
+            put_str(&mut mem, offset + 48, 64, &vh.desc);
+            put_str(&mut mem, offset + 112, 32, &vh.unit);
+        }
+
+        mem[session_info_offset..session_info_offset + self.session_info.len()].copy_from_slice(&self.session_info);
+        self.mem = mem;
+    }
+}
+
+fn encode(value: &VarValue) -> Vec<u8> {
+    match value {
+        VarValue::Char(v) => vec![*v as u8],
+        VarValue::Bool(v) => vec![*v as u8],
+        VarValue::Int(v) => v.to_le_bytes().to_vec(),
+        VarValue::BitField(v) => v.to_le_bytes().to_vec(),
+        VarValue::Float(v) => v.to_le_bytes().to_vec(),
+        VarValue::Double(v) => v.to_le_bytes().to_vec(),
+        VarValue::CharArray(v) => v.iter().map(|&x| x as u8).collect(),
+        VarValue::BoolArray(v) => v.iter().map(|&x| x as u8).collect(),
+        VarValue::IntArray(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+        VarValue::BitFieldArray(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+        VarValue::FloatArray(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+        VarValue::DoubleArray(v) => v.iter().flat_map(|x| x.to_le_bytes()).collect(),
+    }
+}
+
+impl TelemetrySource for FakeSim {
+    fn open(test_file: Option<&str>) -> Result<Self, IRSDKError> {
+        match test_file {
+            Some(_) => Err(IRSDKError::ConnectionFailed("FakeSim does not read dump files".to_string())),
+            None => Ok(FakeSim::new()),
+        }
+    }
+
+    fn memory(&self) -> &[u8] {
+        &self.mem
+    }
+
+    fn wait_for_data(&self, _timeout_ms: u32) -> bool {
+        true
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use futures_core::Stream;
+use tokio::sync::mpsc;
+use crate::handle::{VarHandle, VarScalar, VarSlice};
+use crate::irsdk::IRSDK;
+use crate::time::SessionTime;
+use crate::telemetry::Telemetry;
+use crate::units::Quantity;
+use crate::source::TelemetrySource;
+use crate::structs::VarHeader;
+use crate::value::{typed_var_accessors, FromVarValue, VarError, VarValue};
+
+/// An immutable copy of one var buffer, tagged with the tick it was published
+/// at and its `SessionTime`.
+#[derive(Clone)]
+pub struct Frame {
+    tick_count: i32,
+    session_time: Option<SessionTime>,
+    data: Vec<u8>,
+    var_headers: Arc<HashMap<String, VarHeader<'static>>>,
+}
+
+impl Frame {
+    pub(crate) fn new(tick_count: i32, data: Vec<u8>, var_headers: Arc<HashMap<String, VarHeader<'static>>>) -> Self {
+        let mut frame = Frame { tick_count, session_time: None, data, var_headers };
+        frame.session_time = frame.get_time("SessionTime").ok();
+        frame
+    }
+
+    pub fn tick_count(&self) -> i32 {
+        self.tick_count
+    }
+
+    pub fn session_time(&self) -> Option<SessionTime> {
+        self.session_time
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub fn get(&self, key: &str) -> Option<VarValue> {
+        let var_header = self.var_headers.get(key)?;
+        VarValue::read(&self.data, var_header, var_header.offset as usize)
+    }
+
+    pub fn get_as<T: FromVarValue>(&self, key: &str) -> Result<T, VarError> {
+        if !self.var_headers.contains_key(key) {
+            return Err(VarError::UnknownVar(key.to_string()));
+        }
+        self.get(key)
+            .ok_or_else(|| VarError::NoData(key.to_string()))?
+            .into_typed(key)
+    }
+
+    pub fn get_quantity(&self, key: &str) -> Result<Quantity, VarError> {
+        let var_header = self.var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(key)
+            .ok_or_else(|| VarError::NoData(key.to_string()))?
+            .into_quantity(key, &var_header.unit)
+    }
+
+    pub fn get_time(&self, key: &str) -> Result<SessionTime, VarError> {
+        let var_header = self.var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(key).ok_or_else(|| VarError::NoData(key.to_string()))?.into_time(var_header)
+    }
+
+    typed_var_accessors!(ref);
+
+    pub fn handle<T: VarScalar>(&self, key: &str) -> Result<VarHandle<T>, VarError> {
+        let var_header = self.var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        VarHandle::resolve(var_header)
+    }
+
+    pub fn read<T: VarScalar>(&self, handle: VarHandle<T>) -> Option<T> {
+        handle.read(&self.data)
+    }
+
+    pub fn read_index<T: VarScalar>(&self, handle: VarHandle<T>, index: usize) -> Option<T> {
+        handle.read_index(&self.data, index)
+    }
+
+    /// Resolves every field of a `Telemetry` struct against this frame's vars.
+    pub fn bind<T: Telemetry>(&self) -> Result<T::Handles, VarError> {
+        T::bind(&self.var_headers)
+    }
+
+    pub fn read_telemetry<T: Telemetry>(&self, handles: &T::Handles) -> Result<T, VarError> {
+        T::read(handles, &self.data)
+    }
+
+    pub fn read_slice<T: VarScalar>(&self, handle: VarHandle<T>) -> Option<VarSlice<'_, T>> {
+        handle.read_slice(&self.data)
+    }
+
+    pub fn get_index<T: VarScalar>(&self, key: &str, index: usize) -> Result<T, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_index(handle, index).ok_or_else(|| handle.index_error(key, index))
+    }
+
+    pub fn get_slice<T: VarScalar>(&self, key: &str) -> Result<VarSlice<'_, T>, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_slice(handle).ok_or_else(|| VarError::NoData(key.to_string()))
+    }
+}
+
+/// Blocking iterator returned by `IRSDK::frames`. Yields once per new tick and
+/// ends when the connection is lost or a non-live source has been read.
+pub struct Frames<'a, S: TelemetrySource> {
+    irsdk: &'a mut IRSDK<S>,
+    last_tick_count: Option<i32>,
+}
+
+impl<'a, S: TelemetrySource> Frames<'a, S> {
+    pub(crate) fn new(irsdk: &'a mut IRSDK<S>) -> Self {
+        Frames { irsdk, last_tick_count: None }
+    }
+
+    pub fn irsdk_mut(&mut self) -> &mut IRSDK<S> {
+        self.irsdk
+    }
+}
+
+impl<S: TelemetrySource> Iterator for Frames<'_, S> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let frame = self.irsdk.next_frame(self.last_tick_count, &|| false)?;
+        self.last_tick_count = Some(frame.tick_count());
+        Some(frame)
+    }
+}
+
+/// Async counterpart of `Frames`, fed by a reader thread that owns the `IRSDK`.
+/// It keeps reconnecting until the stream is dropped, and ends after a single
+/// frame for non-live sources.
+pub struct FrameStream {
+    rx: mpsc::Receiver<Frame>,
+}
+
+impl FrameStream {
+    pub(crate) fn new(rx: mpsc::Receiver<Frame>) -> Self {
+        FrameStream { rx }
+    }
+
+    pub async fn recv(&mut self) -> Option<Frame> {
+        self.rx.recv().await
+    }
+}
+
+impl Stream for FrameStream {
+    type Item = Frame;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Frame>> {
+        self.rx.poll_recv(cx)
+    }
+}
@@ -0,0 +1,135 @@
+use std::marker::PhantomData;
+use crate::structs::VarHeader;
+use crate::value::{VarError, VarType};
+
+/// A scalar type a var can be read as without going through `VarValue`.
+pub trait VarScalar: Copy + 'static {
+    const VAR_TYPE: VarType;
+
+    /// Decodes one element from exactly `VAR_TYPE.size()` little-endian bytes.
+    fn from_le_bytes(bytes: &[u8]) -> Self;
+}
+
+macro_rules! impl_var_scalar {
+    ($ty:ty, $var_type:ident) => {
+        impl VarScalar for $ty {
+            const VAR_TYPE: VarType = VarType::$var_type;
+
+            fn from_le_bytes(bytes: &[u8]) -> Self {
+                <$ty>::from_le_bytes(bytes.try_into().unwrap())
+            }
+        }
+    };
+}
+
+impl_var_scalar!(i8, Char);
+impl_var_scalar!(i32, Int);
+impl_var_scalar!(u32, BitField);
+impl_var_scalar!(f32, Float);
+impl_var_scalar!(f64, Double);
+
+impl VarScalar for bool {
+    const VAR_TYPE: VarType = VarType::Bool;
+
+    fn from_le_bytes(bytes: &[u8]) -> Self {
+        bytes[0] != 0
+    }
+}
+
+/// A var resolved once by name, with its type checked and its offset and
+/// count baked in. Reading through a handle does not allocate. Handles are
+/// tied to the var layout they were resolved against; resolve them again
+/// after a reconnect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VarHandle<T> {
+    offset: usize,
+    count: usize,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: VarScalar> VarHandle<T> {
+    pub fn resolve(var_header: &VarHeader<'_>) -> Result<Self, VarError> {
+        let var_type = VarType::try_from(var_header.var_type).ok();
+        if var_type != Some(T::VAR_TYPE) {
+            return Err(VarError::TypeMismatch {
+                name: var_header.name.to_string(),
+                expected: T::VAR_TYPE.name(),
+                found: var_type.map_or("unknown", VarType::name),
+            });
+        }
+        Ok(VarHandle {
+            offset: var_header.offset.max(0) as usize,
+            count: var_header.count.max(0) as usize,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Reads the first element from a var buffer.
+    pub fn read(&self, data: &[u8]) -> Option<T> {
+        self.read_index(data, 0)
+    }
+
+    /// Reads element `index` of an array var; `None` past `count`.
+    pub fn read_index(&self, data: &[u8], index: usize) -> Option<T> {
+        if index >= self.count {
+            return None;
+        }
+        let size = T::VAR_TYPE.size();
+        let start = self.offset + index * size;
+        data.get(start..start + size).map(T::from_le_bytes)
+    }
+
+    /// Borrows all `count` elements from a var buffer.
+    pub fn read_slice<'a>(&self, data: &'a [u8]) -> Option<VarSlice<'a, T>> {
+        let len = self.count * T::VAR_TYPE.size();
+        let bytes = data.get(self.offset..self.offset + len)?;
+        Some(VarSlice { bytes, _marker: PhantomData })
+    }
+
+    pub(crate) fn index_error(&self, name: &str, index: usize) -> VarError {
+        if index >= self.count {
+            VarError::IndexOutOfRange { name: name.to_string(), index, count: self.count }
+        } else {
+            VarError::NoData(name.to_string())
+        }
+    }
+}
+
+/// The elements of an array var, decoded lazily from the var buffer.
+#[derive(Debug, Clone, Copy)]
+pub struct VarSlice<'a, T> {
+    bytes: &'a [u8],
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: VarScalar> VarSlice<'a, T> {
+    pub fn len(&self) -> usize {
+        self.bytes.len() / T::VAR_TYPE.size()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        let size = T::VAR_TYPE.size();
+        let start = index.checked_mul(size)?;
+        self.bytes.get(start..start.checked_add(size)?).map(T::from_le_bytes)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'a {
+        self.bytes.chunks_exact(T::VAR_TYPE.size()).map(T::from_le_bytes)
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().collect()
+    }
+}
@@ -1,25 +1,67 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
-use memmap2::MmapOptions;
+use memmap2::{Mmap, MmapOptions};
 use crate::constants::*;
 use crate::structs::*;
+use crate::value::{FromVarValue, VarError, VarValue};
+use crate::handle::{VarHandle, VarScalar, VarSlice};
+use crate::time::SessionTime;
+use crate::telemetry::Telemetry;
+use crate::units::Quantity;
+use crate::session::{ParsedSessionInfo, SessionDocument};
+use crate::sanitize::YamlFix;
+use crate::setup::CarSetupTree;
+use crate::query::{QueryError, SessionQuery};
 
 #[derive(Debug)]
 pub enum IBTError {
     NotInitialized,
     FileAccessError(String),
     MemoryAccessError,
+    ParseError(ParseError),
+}
+
+impl fmt::Display for IBTError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IBTError::NotInitialized => write!(f, "no telemetry file is open"),
+            IBTError::FileAccessError(message) => write!(f, "cannot access telemetry file: {}", message),
+            IBTError::MemoryAccessError => write!(f, "cannot map telemetry file"),
+            IBTError::ParseError(e) => write!(f, "invalid telemetry file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for IBTError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IBTError::ParseError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ParseError> for IBTError {
+    fn from(e: ParseError) -> Self {
+        IBTError::ParseError(e)
+    }
 }
 
 pub struct IBT {
     ibt_file: Option<File>,
     shared_mem: Option<Mmap>,
-    header: Option<Header>,
     disk_header: Option<DiskSubHeader>,
-    var_headers: Option<Vec<VarHeader>>,
-    var_headers_dict: Option<HashMap<String, VarHeader>>,
+    var_headers: Option<Vec<VarHeader<'static>>>,
+    var_headers_dict: Option<HashMap<String, VarHeader<'static>>>,
     var_headers_names: Option<Vec<String>>,
-    session_info_dict: Option<HashMap<String, serde_yaml::Value>>,
+    session_info_parsed: Option<ParsedSessionInfo>,
+}
+
+impl Default for IBT {
+    fn default() -> Self {
+        IBT::new()
+    }
 }
 
 impl IBT {
@@ -27,12 +69,11 @@ impl IBT {
         IBT {
             ibt_file: None,
             shared_mem: None,
-            header: None,
             disk_header: None,
             var_headers: None,
             var_headers_dict: None,
             var_headers_names: None,
-            session_info_dict: None,
+            session_info_parsed: None,
         }
     }
 
@@ -40,47 +81,165 @@ impl IBT {
         self.ibt_file = Some(File::open(ibt_file).map_err(|e| IBTError::FileAccessError(e.to_string()))?);
         self.shared_mem = Some(unsafe {
             MmapOptions::new()
-                .map(&self.ibt_file.as_ref().unwrap())
+                .map(self.ibt_file.as_ref().unwrap())
                 .map_err(|e| IBTError::FileAccessError(e.to_string()))?
         });
         if let Some(shared_mem) = &self.shared_mem {
-            let irsdk_struct = IRSDKStruct::new(shared_mem.clone(), 0);
-            self.header = Some(Header::from_struct(&irsdk_struct));
-            self.disk_header = Some(DiskSubHeader::from_struct(&irsdk_struct, 112));
+            let header = Header::parse(&shared_mem[..])?;
+            let disk_header = DiskSubHeader::parse(&shared_mem[..], HEADER_SIZE)?;
+            if let Some(var_buf) = header.var_buf(0) {
+                check_region(
+                    &shared_mem[..],
+                    "records",
+                    var_buf.buf_offset as i64,
+                    disk_header.session_record_count as i64 * header.buf_len as i64,
+                )?;
+            }
+            self.disk_header = Some(disk_header);
+
+            let var_headers: Vec<VarHeader<'static>> = header.var_headers().map(VarHeader::into_owned).collect();
+            self.var_headers_dict = Some(var_headers.iter().map(|vh| (vh.name.to_string(), vh.clone())).collect());
+            self.var_headers_names = Some(var_headers.iter().map(|vh| vh.name.to_string()).collect());
+            self.var_headers = Some(var_headers);
+
+            if let Some(binary_data) = header.session_info() {
+                self.session_info_parsed = ParsedSessionInfo::parse(header.session_info_update, binary_data).ok();
+            }
         }
         Ok(())
     }
 
+    pub fn var_headers_names(&self) -> Vec<String> {
+        self.var_headers_names.clone().unwrap_or_default()
+    }
+
+    pub fn session_info(&self) -> Option<&SessionDocument> {
+        self.session_info_parsed.as_ref()?.document.as_ref()
+    }
+
+    pub fn parsed_session_info(&self) -> Option<&ParsedSessionInfo> {
+        self.session_info_parsed.as_ref()
+    }
+
+    pub fn session_query(&self, query: &str) -> Result<Option<&serde_yaml::Value>, QueryError> {
+        let query = SessionQuery::parse(query)?;
+        Ok(self.session_info_parsed.as_ref().and_then(|parsed| query.eval(&parsed.value)))
+    }
+
+    /// The car setup the file was recorded with, values parsed.
+    pub fn car_setup(&self) -> Option<CarSetupTree> {
+        self.session_info_parsed.as_ref()?.car_setup()
+    }
+
+    /// What the sanitizer had to repair in the file's session info.
+    pub fn session_info_fixes(&self) -> &[YamlFix] {
+        self.session_info_parsed.as_ref().map_or(&[], |parsed| &parsed.fixes)
+    }
+
+    pub fn record_count(&self) -> i32 {
+        self.disk_header.as_ref().map_or(0, |dh| dh.session_record_count)
+    }
+
+    fn header(&self) -> Option<Header<'_>> {
+        self.shared_mem.as_ref().and_then(|shared_mem| Header::parse(&shared_mem[..]).ok())
+    }
+
     pub fn close(&mut self) {
         self.shared_mem = None;
         self.ibt_file = None;
-        self.header = None;
         self.disk_header = None;
         self.var_headers = None;
         self.var_headers_dict = None;
         self.var_headers_names = None;
-        self.session_info_dict = None;
+        self.session_info_parsed = None;
     }
 
-    pub fn get(&self, index: i32, key: &str) -> Option<serde_yaml::Value> {
-        if self.header.is_none() || self.disk_header.is_none() {
-            return None;
+    pub fn get(&self, index: i32, key: &str) -> Option<VarValue> {
+        let var_header = self.var_headers_dict.as_ref()?.get(key)?;
+        VarValue::read(self.record(index)?, var_header, var_header.offset as usize)
+    }
+
+    pub fn get_as<T: FromVarValue>(&self, index: i32, key: &str) -> Result<T, VarError> {
+        if !self.var_headers_dict.as_ref().is_some_and(|dict| dict.contains_key(key)) {
+            return Err(VarError::UnknownVar(key.to_string()));
         }
-        let disk_header = self.disk_header.as_ref().unwrap();
-        if index < 0 || index >= disk_header.session_record_count {
+        self.get(index, key)
+            .ok_or_else(|| VarError::NoData(format!("{} at record {}", key, index)))?
+            .into_typed(key)
+    }
+
+    pub fn get_quantity(&self, index: i32, key: &str) -> Result<Quantity, VarError> {
+        let var_header = self
+            .var_headers_dict
+            .as_ref()
+            .and_then(|dict| dict.get(key))
+            .ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(index, key)
+            .ok_or_else(|| VarError::NoData(format!("{} at record {}", key, index)))?
+            .into_quantity(key, &var_header.unit)
+    }
+
+    pub fn get_time(&self, index: i32, key: &str) -> Result<SessionTime, VarError> {
+        let var_header = self
+            .var_headers_dict
+            .as_ref()
+            .and_then(|dict| dict.get(key))
+            .ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(index, key)
+            .ok_or_else(|| VarError::NoData(format!("{} at record {}", key, index)))?
+            .into_time(var_header)
+    }
+
+    pub fn handle<T: VarScalar>(&self, key: &str) -> Result<VarHandle<T>, VarError> {
+        let var_header = self
+            .var_headers_dict
+            .as_ref()
+            .and_then(|dict| dict.get(key))
+            .ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        VarHandle::resolve(var_header)
+    }
+
+    pub fn read<T: VarScalar>(&self, index: i32, handle: VarHandle<T>) -> Option<T> {
+        handle.read(self.record(index)?)
+    }
+
+    pub fn read_index<T: VarScalar>(&self, index: i32, handle: VarHandle<T>, element: usize) -> Option<T> {
+        handle.read_index(self.record(index)?, element)
+    }
+
+    /// Resolves every field of a `Telemetry` struct against the file's vars.
+    pub fn bind<T: Telemetry>(&self) -> Result<T::Handles, VarError> {
+        let var_headers = self.var_headers_dict.as_ref().ok_or_else(|| VarError::NoData("var headers".to_string()))?;
+        T::bind(var_headers)
+    }
+
+    pub fn read_telemetry<T: Telemetry>(&self, index: i32, handles: &T::Handles) -> Result<T, VarError> {
+        let record = self.record(index).ok_or_else(|| VarError::NoData(format!("record {}", index)))?;
+        T::read(handles, record)
+    }
+
+    pub fn read_slice<T: VarScalar>(&self, index: i32, handle: VarHandle<T>) -> Option<VarSlice<'_, T>> {
+        handle.read_slice(self.record(index)?)
+    }
+
+    pub fn get_index<T: VarScalar>(&self, index: i32, key: &str, element: usize) -> Result<T, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_index(index, handle, element)
+            .ok_or_else(|| handle.index_error(key, element))
+    }
+
+    pub fn get_slice<T: VarScalar>(&self, index: i32, key: &str) -> Result<VarSlice<'_, T>, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_slice(index, handle)
+            .ok_or_else(|| VarError::NoData(format!("{} at record {}", key, index)))
+    }
+
+    fn record(&self, index: i32) -> Option<&[u8]> {
+        if index < 0 || index >= self.record_count() {
             return None;
         }
-        if let Some(var_headers_dict) = &self.var_headers_dict {
-            if let Some(var_header) = var_headers_dict.get(key) {
-                if let Some(shared_mem) = &self.shared_mem {
-                    if let Some(header) = &self.header {
-                        let var_offset = var_header.offset + header.var_buf[0].buf_offset + index * header.buf_len;
-                        // Чтение данных в зависимости от типа
-                        // ...
-                    }
-                }
-            }
-        }
-        None
+        let header = self.header()?;
+        let start = header.var_buf(0)?.buf_offset as usize + index as usize * header.buf_len as usize;
+        header.memory().get(start..start + header.buf_len as usize)
     }
 }
@@ -1,24 +1,34 @@
 use std::collections::HashMap;
+use std::fmt;
 use std::fs::File;
-use std::io::{Read, Write};
-use std::path::Path;
+use std::io::Write;
+use std::sync::Arc;
 use std::thread;
-use std::str;
-use memmap2::MmapOptions;
-use regex::Regex;
-use reqwest::blocking::Client;
+use std::time::{Duration, Instant};
 use serde_yaml;
-use windows::Win32::System::Threading::*;
-use windows::Win32::UI::WindowsAndMessaging::*;
-use windows::Win32::Foundation::*;
 use crate::constants::*;
 use crate::structs::*;
-use windows::Win32::System::Threading::{OpenEventW, WaitForSingleObject, SYNCHRONIZE};
-use windows::Win32::UI::WindowsAndMessaging::{RegisterWindowMessageW, SendNotifyMessageW, HWND_BROADCAST};
-use windows::Win32::System::Memory::{CreateFileMappingW, MapViewOfFile, FILE_MAP_READ, INVALID_HANDLE_VALUE};
+use crate::value::{typed_var_accessors, FromVarValue, VarError, VarValue};
+use crate::session::{ParsedSessionInfo, SessionDocument};
+use crate::session_worker::SessionWorker;
+use crate::session_diff::{diff_session_info, SessionInfoEvent};
+use crate::session_archive::SessionArchive;
+use crate::setup::CarSetupTree;
+use crate::sanitize::YamlFix;
+use crate::query::{QueryError, SessionQuery};
+use crate::source::{DefaultSource, TelemetrySource};
+use crate::connection::{ConnectionEvent, ConnectionState};
+use crate::frame::{Frame, FrameStream, Frames};
+use crate::handle::{VarHandle, VarScalar, VarSlice};
+use crate::time::SessionTime;
+use crate::telemetry::Telemetry;
+use crate::units::Quantity;
+#[cfg(windows)]
 use windows::core::PCWSTR;
-use std::ptr;
-use std::slice;
+#[cfg(windows)]
+use windows::Win32::Foundation::{LPARAM, WPARAM};
+#[cfg(windows)]
+use windows::Win32::UI::WindowsAndMessaging::{RegisterWindowMessageW, SendNotifyMessageW, HWND_BROADCAST};
 
 
 #[derive(Debug)]
@@ -28,87 +38,166 @@ pub enum IRSDKError {
     MemoryAccessError,
     YamlParseError(String),
     WindowsAPIError(String),
+    ParseError(ParseError),
 }
 
-pub struct SessionData {
-    data: Option<serde_yaml::Value>,
-    data_last: Option<serde_yaml::Value>,
-    data_binary: Option<Vec<u8>>,
-    update: Option<i32>,
-    async_session_info_update: Option<i32>,
+impl fmt::Display for IRSDKError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IRSDKError::NotInitialized => write!(f, "not connected to the sim"),
+            IRSDKError::ConnectionFailed(message) => write!(f, "connection failed: {}", message),
+            IRSDKError::MemoryAccessError => write!(f, "cannot map telemetry memory"),
+            IRSDKError::YamlParseError(message) => write!(f, "session info parse error: {}", message),
+            IRSDKError::WindowsAPIError(message) => write!(f, "Windows API error: {}", message),
+            IRSDKError::ParseError(e) => write!(f, "invalid telemetry memory: {}", e),
+        }
+    }
 }
 
-impl SessionData {
-    fn new() -> Self {
-        SessionData {
-            data: None,
-            data_last: None,
-            data_binary: None,
-            update: None,
-            async_session_info_update: None,
+impl std::error::Error for IRSDKError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            IRSDKError::ParseError(e) => Some(e),
+            _ => None,
         }
     }
 }
 
-pub struct IRSDK {
+impl From<ParseError> for IRSDKError {
+    fn from(e: ParseError) -> Self {
+        IRSDKError::ParseError(e)
+    }
+}
+
+// SessionNum and SessionTime, each `None` if the sim does not provide it.
+type ArchiveHandles = (Option<VarHandle<i32>>, Option<VarHandle<f64>>);
+
+pub struct IRSDK<S: TelemetrySource = DefaultSource> {
     parse_yaml_async: bool,
     is_initialized: bool,
-    last_session_info_update: i32,
-    shared_mem: Option<Mmap>,
-    header: Option<Header>,
-    data_valid_event: Option<HANDLE>,
-    var_headers: Option<Vec<VarHeader>>,
-    var_headers_dict: Option<HashMap<String, VarHeader>>,
+    source: Option<S>,
+    // Whether `startup` opened the source, rather than the caller passing it
+    // to `with_source`. Only an opened source is dropped when the sim goes away.
+    owns_source: bool,
+    var_headers: Option<Vec<VarHeader<'static>>>,
+    var_headers_dict: Option<Arc<HashMap<String, VarHeader<'static>>>>,
     var_headers_names: Option<Vec<String>>,
-    var_buffer_latest: Option<VarBuffer>,
-    session_info_dict: HashMap<String, SessionData>,
+    var_buffer_latest: Option<Frame>,
+    buffer_layout: Option<BufferLayout>,
+    session_info_parsed: Option<Arc<ParsedSessionInfo>>,
+    // The update whose string failed to parse, and why.
+    session_info_error: Option<(i32, serde_yaml::Error)>,
+    session_info_requested_update: Option<i32>,
+    session_worker: Option<SessionWorker>,
+    session_info_events: Vec<SessionInfoEvent>,
+    session_archive: Option<SessionArchive>,
+    // Resolved on the first archived tick, not per tick by name.
+    archive_handles: Option<ArchiveHandles>,
     broadcast_msg_id: Option<u32>,
-    test_file: Option<File>,
-    workaround_connected_state: i32,
+    connection_state: ConnectionState,
+    connection_events: Vec<ConnectionEvent>,
+    last_tick_count: i32,
+    last_tick_at: Instant,
+    stale_timeout: Duration,
+    reconnect_interval: Duration,
+    last_startup_attempt: Option<Instant>,
 }
 
+const SNAPSHOT_ATTEMPTS: usize = 4;
+
 impl IRSDK {
     pub fn new(parse_yaml_async: bool) -> Self {
+        IRSDK::with_source(parse_yaml_async, None)
+    }
+}
+
+impl<S: TelemetrySource> IRSDK<S> {
+    pub fn with_source(parse_yaml_async: bool, source: Option<S>) -> Self {
         IRSDK {
             parse_yaml_async,
             is_initialized: false,
-            last_session_info_update: 0,
-            shared_mem: None,
-            header: None,
-            data_valid_event: None,
+            source,
+            owns_source: false,
             var_headers: None,
             var_headers_dict: None,
             var_headers_names: None,
             var_buffer_latest: None,
-            session_info_dict: HashMap::new(),
+            buffer_layout: None,
+            session_info_parsed: None,
+            session_info_error: None,
+            session_info_requested_update: None,
+            session_worker: None,
+            session_info_events: Vec::new(),
+            session_archive: None,
+            archive_handles: None,
             broadcast_msg_id: None,
-            test_file: None,
-            workaround_connected_state: 0,
+            connection_state: ConnectionState::Disconnected,
+            connection_events: Vec::new(),
+            last_tick_count: 0,
+            last_tick_at: Instant::now(),
+            stale_timeout: Duration::from_secs(1),
+            reconnect_interval: Duration::from_secs(1),
+            last_startup_attempt: None,
         }
     }
 
     pub fn is_connected(&mut self) -> bool {
-        if let Some(header) = &self.header {
-            if header.status == STATUS_CONNECTED {
-                self.workaround_connected_state = 0;
-            }
-            if self.workaround_connected_state == 0 && header.status != STATUS_CONNECTED {
-                self.workaround_connected_state = 1;
-            }
-            if self.workaround_connected_state == 1 && (self.get("SessionNum").is_none() || self.test_file.is_some()) {
-                self.workaround_connected_state = 2;
+        matches!(self.update_connection(), ConnectionState::Connected | ConnectionState::Stale)
+    }
+
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state
+    }
+
+    pub fn take_connection_events(&mut self) -> Vec<ConnectionEvent> {
+        std::mem::take(&mut self.connection_events)
+    }
+
+    pub fn set_stale_timeout(&mut self, stale_timeout: Duration) {
+        self.stale_timeout = stale_timeout;
+    }
+
+    pub fn set_reconnect_interval(&mut self, reconnect_interval: Duration) {
+        self.reconnect_interval = reconnect_interval;
+    }
+
+    pub fn update_connection(&mut self) -> ConnectionState {
+        match self.connection_state {
+            ConnectionState::Disconnected => {}
+            ConnectionState::WaitingForSim => {
+                let due = self.last_startup_attempt.is_none_or(|at| at.elapsed() >= self.reconnect_interval);
+                if due {
+                    self.last_startup_attempt = Some(Instant::now());
+                    let _ = self.startup(None, None);
+                }
             }
-            if self.workaround_connected_state == 2 && self.get("SessionNum").is_some() {
-                self.workaround_connected_state = 3;
+            ConnectionState::Connected | ConnectionState::Stale => {
+                if !self.sim_running() {
+                    self.reset();
+                    self.set_connection_state(ConnectionState::WaitingForSim);
+                } else if self.source.as_ref().is_some_and(|s| s.is_live()) {
+                    self.refresh_buffer_layout();
+                    let tick_count = self.latest_tick_count();
+                    if tick_count != self.last_tick_count {
+                        self.last_tick_count = tick_count;
+                        self.last_tick_at = Instant::now();
+                        self.set_connection_state(ConnectionState::Connected);
+                    } else if self.last_tick_at.elapsed() >= self.stale_timeout {
+                        self.set_connection_state(ConnectionState::Stale);
+                    }
+                }
+                // Pick up every session info bump, not only the ones an
+                // accessor happens to run after.
+                if self.is_initialized {
+                    self.refresh_session_info();
+                }
             }
         }
-        self.header.is_some() && 
-        (self.test_file.is_some() || self.data_valid_event.is_some()) && 
-        (self.header.as_ref().map_or(false, |h| h.status == STATUS_CONNECTED) || self.workaround_connected_state == 3)
+        self.connection_state
     }
 
     pub fn session_info_update(&self) -> i32 {
-        self.header.as_ref().map_or(0, |h| h.session_info_update)
+        self.header().map_or(0, |h| h.session_info_update)
     }
 
     pub fn var_headers_names(&mut self) -> Vec<String> {
@@ -116,7 +205,7 @@ impl IRSDK {
             self.var_headers_names = Some(
                 self.var_headers()
                     .iter()
-                    .map(|vh| vh.name.clone())
+                    .map(|vh| vh.name.to_string())
                     .collect()
             );
         }
@@ -124,126 +213,99 @@ impl IRSDK {
     }
 
     pub fn startup(&mut self, test_file: Option<&str>, dump_to: Option<&str>) -> Result<bool, IRSDKError> {
-        if test_file.is_none() {
-            if !self.check_sim_status() {
-                return Err(IRSDKError::ConnectionFailed("IRacing is not running".to_string()));
-            }
-            let event_name = format!("{}\0", DATA_VALID_EVENT_NAME);
-            let event_name_w: Vec<u16> = event_name.encode_utf16().chain(std::iter::once(0)).collect();
-            self.data_valid_event = unsafe {
-                let handle = OpenEventW(SYNCHRONIZE.0, FALSE, PCWSTR(event_name_w.as_ptr()));
-                if handle.0 == 0 {
-                    return Err(IRSDKError::WindowsAPIError("Failed to open event".to_string()));
+        if self.source.is_none() {
+            match S::open(test_file) {
+                Ok(source) => {
+                    self.source = Some(source);
+                    self.owns_source = true;
                 }
-                Some(handle)
-            };
+                Err(e) => {
+                    self.set_connection_state(ConnectionState::WaitingForSim);
+                    return Err(e);
+                }
+            }
         }
 
         if !self.wait_valid_data_event() {
-            self.data_valid_event = None;
+            self.drop_source();
+            self.set_connection_state(ConnectionState::WaitingForSim);
             return Err(IRSDKError::ConnectionFailed("Failed to wait for valid data event".to_string()));
         }
 
-        if self.shared_mem.is_none() {
-            match test_file {
-                Some(path) => {
-                    let file = File::open(path).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
-                    self.test_file = Some(file);
-                    self.shared_mem = Some(unsafe {
-                        MmapOptions::new()
-                            .map(&self.test_file.as_ref().unwrap())
-                            .map_err(|_| IRSDKError::MemoryAccessError)?
-                    });
-                }
-                None => {
-                    let map_name = format!("{}\0", MEM_MAP_FILE);
-                    let map_name_w: Vec<u16> = map_name.encode_utf16().chain(std::iter::once(0)).collect();
-                    unsafe {
-                        let handle = CreateFileMappingW(
-                            INVALID_HANDLE_VALUE,
-                            ptr::null_mut(),
-                            FILE_MAP_READ.0,
-                            0,
-                            MEM_MAP_FILE_SIZE as u32,
-                            PCWSTR(map_name_w.as_ptr()),
-                        );
-                        if handle.0 == 0 {
-                            return Err(IRSDKError::WindowsAPIError("Failed to create file mapping".to_string()));
-                        }
-
-                        let view = MapViewOfFile(
-                            handle,
-                            FILE_MAP_READ.0,
-                            0,
-                            0,
-                            MEM_MAP_FILE_SIZE,
-                        );
-                        if view.is_null() {
-                            return Err(IRSDKError::WindowsAPIError("Failed to map view of file".to_string()));
-                        }
-
-                        let slice = slice::from_raw_parts(view as *const u8, MEM_MAP_FILE_SIZE);
-                        self.shared_mem = Some(Mmap::from(slice.to_vec())); // Unsafe conversion to Mmap, needs to be redone
-                    }
-                }
-            }
-        }
-
-        if let Some(mem) = &self.shared_mem {
+        if let Some(source) = &self.source {
+            let mem = source.memory();
             if let Some(dump_path) = dump_to {
                 let mut f = File::create(dump_path).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
-                f.write_all(&mem[..]).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
+                f.write_all(mem).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
             }
-            let irsdk_struct = IRSDKStruct::new(mem.clone(), 0);
-            self.header = Some(Header::from_struct(&irsdk_struct));
-            if let Some(header) = &self.header {
-                self.is_initialized = header.version >= 1 && !header.var_buf.is_empty();
+            match Header::parse(mem) {
+                Ok(header) => {
+                    self.is_initialized = header.version >= 1 && header.num_buf > 0;
+                    self.buffer_layout = Some(BufferLayout::new(&header));
+                }
+                Err(e) => {
+                    self.reset();
+                    self.set_connection_state(ConnectionState::WaitingForSim);
+                    return Err(e.into());
+                }
             }
         }
 
+        if self.sim_running() {
+            self.last_tick_count = self.latest_tick_count();
+            self.last_tick_at = Instant::now();
+            self.set_connection_state(ConnectionState::Connected);
+        } else {
+            self.set_connection_state(ConnectionState::WaitingForSim);
+        }
         Ok(self.is_initialized)
     }
 
+    pub fn shutdown(&mut self) {
+        self.reset();
+        self.source = None;
+        self.set_connection_state(ConnectionState::Disconnected);
+    }
 
+    fn drop_source(&mut self) {
+        if self.owns_source {
+            self.source = None;
+        }
+    }
 
-    pub fn shutdown(&mut self) {
+    fn reset(&mut self) {
         self.is_initialized = false;
-        self.last_session_info_update = 0;
-        self.shared_mem = None;
-        self.header = None;
-        self.data_valid_event = None;
+        self.drop_source();
         self.var_headers = None;
         self.var_headers_dict = None;
         self.var_headers_names = None;
         self.var_buffer_latest = None;
-        self.session_info_dict.clear();
+        self.buffer_layout = None;
+        self.session_info_parsed = None;
+        self.session_info_error = None;
+        self.session_info_requested_update = None;
+        self.session_worker = None;
+        self.archive_handles = None;
         self.broadcast_msg_id = None;
-        if self.test_file.is_some() {
-            self.test_file = None;
-        }
     }
 
 
-    pub fn parse_to(&self, to_file: &str) -> Result<(), IRSDKError> {
+    pub fn parse_to(&mut self, to_file: &str) -> Result<(), IRSDKError> {
         if !self.is_initialized {
             return Err(IRSDKError::NotInitialized);
         }
         let mut f = File::create(to_file).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
-        if let Some(header) = &self.header {
-            if let Some(shared_mem) = &self.shared_mem {
-                let session_data = &shared_mem[header.session_info_offset as usize..(header.session_info_offset + header.session_info_len) as usize];
-                f.write_all(session_data).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
-                let var_headers_dict = self.var_headers_dict.as_ref().unwrap_or(&HashMap::new());
-                let mut lines = Vec::new();
-                for key in var_headers_dict.keys() {
-                    if let Some(value) = self.get(key) {
-                        lines.push(format!("{:32}{}", key, value));
-                    }
-                }
-                lines.sort_by(|a, b| a.to_lowercase().cmp(&b.to_lowercase()));
-                f.write_all(lines.join("\n").as_bytes()).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
+        if let Some(session_data) = self.session_info_bytes() {
+            f.write_all(session_data).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
+        }
+        let mut lines = Vec::new();
+        for key in self.var_headers_names() {
+            if let Some(value) = self.get(&key) {
+                lines.push(format!("{:32}{}", key, value));
             }
         }
+        lines.sort_by_key(|a| a.to_lowercase());
+        f.write_all(lines.join("\n").as_bytes()).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
         Ok(())
     }
 
@@ -313,261 +375,411 @@ impl IRSDK {
         self.broadcast_msg(broadcast_msg::VIDEO_CAPTURE, video_capture_mode as i32, 0, 0)
     }
 
-    pub fn get(&mut self, key: &str) -> Option<String> {
-        if let Some(var_headers_dict) = &self.var_headers_dict {
-            if let Some(var_header) = var_headers_dict.get(key) {
-                if let Some(var_buf_latest) = &self.var_buffer_latest {
-                    if let Some(shared_mem) = &self.shared_mem {
-                        let mem = var_buf_latest.get_memory(shared_mem);
-                        let offset = var_buf_latest.get_buf_offset() as usize + var_header.offset as usize;
-                        let type_size = match var_header.var_type {
-                            0 => 1,
-                            1 => 1,
-                            2 => 4,
-                            3 => 4,
-                            4 => 4,
-                            5 => 8,
-                            _ => return None,
-                        };
-                        let total_size = type_size * var_header.count as usize;
-                        if offset + total_size > mem.len() {
-                            return None;
-                        }
-                        let mut values = Vec::new();
-                        for i in 0..var_header.count as usize {
-                            let start = offset + i * type_size;
-                            let value = match var_header.var_type {
-                                0 => mem[start].to_string(),
-                                1 => (mem[start] != 0).to_string(),
-                                2 => i32::from_le_bytes(mem[start..start + 4].try_into().unwrap()).to_string(),
-                                3 => u32::from_le_bytes(mem[start..start + 4].try_into().unwrap()).to_string(),
-                                4 => f32::from_le_bytes(mem[start..start + 4].try_into().unwrap()).to_string(),
-                                5 => f64::from_le_bytes(mem[start..start + 8].try_into().unwrap()).to_string(),
-                                _ => return None,
-                            };
-                            values.push(value);
-                        }
-                        return if var_header.count == 1 {
-                            Some(values[0].clone())
-                        } else {
-                            Some(format!("[{}]", values.join(", ")))
-                        };
-                    }
-                }
-            }
+    pub fn get(&mut self, key: &str) -> Option<VarValue> {
+        let var_headers = self.var_headers_dict();
+        let var_header = var_headers.get(key)?;
+        VarValue::read(self.latest_data()?, var_header, var_header.offset as usize)
+    }
+
+    pub fn get_as<T: FromVarValue>(&mut self, key: &str) -> Result<T, VarError> {
+        if !self.var_headers_dict().contains_key(key) {
+            return Err(VarError::UnknownVar(key.to_string()));
         }
-        self.get_session_info(key).map(|v| v.to_string())
+        self.get(key)
+            .ok_or_else(|| VarError::NoData(key.to_string()))?
+            .into_typed(key)
+    }
+
+    /// A numeric scalar together with the unit from its var header.
+    pub fn get_quantity(&mut self, key: &str) -> Result<Quantity, VarError> {
+        let var_headers = self.var_headers_dict();
+        let var_header = var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(key)
+            .ok_or_else(|| VarError::NoData(key.to_string()))?
+            .into_quantity(key, &var_header.unit)
+    }
+
+    pub fn get_time(&mut self, key: &str) -> Result<SessionTime, VarError> {
+        let var_headers = self.var_headers_dict();
+        let var_header = var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        self.get(key).ok_or_else(|| VarError::NoData(key.to_string()))?.into_time(var_header)
+    }
+
+    typed_var_accessors!(mut);
+
+    pub fn handle<T: VarScalar>(&mut self, key: &str) -> Result<VarHandle<T>, VarError> {
+        let var_headers = self.var_headers_dict();
+        let var_header = var_headers.get(key).ok_or_else(|| VarError::UnknownVar(key.to_string()))?;
+        VarHandle::resolve(var_header)
+    }
+
+    /// Reads through `handle` from the frozen frame, or else from the latest
+    /// complete var buffer, without allocating.
+    pub fn read<T: VarScalar>(&self, handle: VarHandle<T>) -> Option<T> {
+        handle.read(self.latest_data()?)
+    }
+
+    pub fn read_index<T: VarScalar>(&self, handle: VarHandle<T>, index: usize) -> Option<T> {
+        handle.read_index(self.latest_data()?, index)
+    }
+
+    /// Resolves every field of a `Telemetry` struct against the current vars.
+    pub fn bind<T: Telemetry>(&mut self) -> Result<T::Handles, VarError> {
+        T::bind(&self.var_headers_dict())
+    }
+
+    pub fn read_telemetry<T: Telemetry>(&self, handles: &T::Handles) -> Result<T, VarError> {
+        T::read(handles, self.latest_data().ok_or_else(|| VarError::NoData("var buffer".to_string()))?)
+    }
+
+    pub fn read_slice<T: VarScalar>(&self, handle: VarHandle<T>) -> Option<VarSlice<'_, T>> {
+        handle.read_slice(self.latest_data()?)
+    }
+
+    /// Element `index` of an array var such as `CarIdxLapDistPct`.
+    pub fn get_index<T: VarScalar>(&mut self, key: &str, index: usize) -> Result<T, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_index(handle, index).ok_or_else(|| handle.index_error(key, index))
+    }
+
+    pub fn get_slice<T: VarScalar>(&mut self, key: &str) -> Result<VarSlice<'_, T>, VarError> {
+        let handle = self.handle::<T>(key)?;
+        self.read_slice(handle).ok_or_else(|| VarError::NoData(key.to_string()))
     }
 
     pub fn freeze_var_buffer_latest(&mut self) {
         self.unfreeze_var_buffer_latest();
         self.wait_valid_data_event();
-        if let Some(header) = &self.header {
-            if let Some(shared_mem) = &self.shared_mem {
-                let mut latest = header.var_buf.clone();
-                latest.sort_by_key(|v| v.tick_count);
-                latest.reverse();
-                if let Some(mut var_buf) = latest.get(0).cloned() {
-                    var_buf.freeze(shared_mem);
-                    self.var_buffer_latest = Some(var_buf);
-                }
-            }
-        }
+        self.var_buffer_latest = self.snapshot();
     }
 
     pub fn unfreeze_var_buffer_latest(&mut self) {
-        if let Some(mut var_buf) = self.var_buffer_latest.take() {
-            var_buf.unfreeze();
-            self.var_buffer_latest = None;
+        self.var_buffer_latest = None;
+    }
+
+    /// Copies the newest var buffer into an immutable `Frame`. The buffer's
+    /// tick count is read before and after the copy and the copy is retried if
+    /// the sim rotated into that buffer meanwhile; gives up after
+    /// `SNAPSHOT_ATTEMPTS`.
+    pub fn snapshot(&mut self) -> Option<Frame> {
+        let var_headers = self.var_headers_dict();
+        for _ in 0..SNAPSHOT_ATTEMPTS {
+            if let Some((tick_count, data)) = self.header()?.copy_latest_var_buf(<[u8]>::to_vec) {
+                return Some(Frame::new(tick_count, data, var_headers));
+            }
         }
+        None
     }
 
-    pub fn get_session_info_update_by_key(&self, key: &str) -> Option<i32> {
-        self.session_info_dict.get(key).and_then(|data| data.update)
+    pub fn frames(&mut self) -> Frames<'_, S> {
+        Frames::new(self)
+    }
+
+    pub fn frame_stream(mut self) -> FrameStream
+    where
+        S: Send + 'static,
+    {
+        let (tx, rx) = tokio::sync::mpsc::channel(2);
+        thread::spawn(move || {
+            let mut last_tick_count = None;
+            while !tx.is_closed() {
+                match self.next_frame(last_tick_count, &|| tx.is_closed()) {
+                    Some(frame) => {
+                        last_tick_count = Some(frame.tick_count());
+                        if tx.blocking_send(frame).is_err() {
+                            break;
+                        }
+                    }
+                    None => {
+                        let live = self.source.as_ref().is_none_or(|s| s.is_live());
+                        if self.connection_state == ConnectionState::Disconnected || !live {
+                            break;
+                        }
+                        thread::sleep(self.reconnect_interval.min(Duration::from_millis(100)));
+                    }
+                }
+            }
+        });
+        FrameStream::new(rx)
     }
 
-    fn check_sim_status(&self) -> bool {
-        let client = Client::new();
-        match client.get(SIM_STATUS_URL).send() {
-            Ok(response) => {
-                if let Ok(text) = response.text() {
-                    return text.contains("running:1");
+    /// Waits for a tick newer than `after`. Gives up with `None` once
+    /// `cancelled` returns true, e.g. when nobody is listening any more.
+    pub(crate) fn next_frame(&mut self, after: Option<i32>, cancelled: &dyn Fn() -> bool) -> Option<Frame> {
+        loop {
+            if !matches!(self.update_connection(), ConnectionState::Connected | ConnectionState::Stale) {
+                return None;
+            }
+            if after != Some(self.latest_tick_count()) {
+                if let Some(frame) = self.snapshot() {
+                    return Some(frame);
                 }
-                false
             }
-            Err(e) => {
-                println!("Failed connect to IRacing: {}", e);
-                false
+            // Either nothing new yet or every copy was torn by the producer;
+            // a live sim will publish another tick.
+            if !self.source.as_ref().is_some_and(|s| s.is_live()) || cancelled() {
+                return None;
             }
+            self.wait_valid_data_event();
         }
     }
 
-    fn wait_valid_data_event(&self) -> bool {
-        if let Some(event) = self.data_valid_event {
-            unsafe {
-                WaitForSingleObject(event, 32) == 0
-            }
-        } else {
-            true
+    pub fn get_session_info_update_by_key(&self, key: &str) -> Option<i32> {
+        let parsed = self.session_info_parsed.as_ref()?;
+        parsed.value.get(key).map(|_| parsed.update)
+    }
+
+    pub fn source(&self) -> Option<&S> {
+        self.source.as_ref()
+    }
+
+    pub fn source_mut(&mut self) -> Option<&mut S> {
+        self.source.as_mut()
+    }
+
+    fn sim_running(&self) -> bool {
+        self.is_initialized
+            && (self.header().is_some_and(|h| h.status == STATUS_CONNECTED)
+                || self.source.as_ref().is_some_and(|s| !s.is_live()))
+    }
+
+    fn latest_tick_count(&self) -> i32 {
+        let Some(source) = &self.source else { return 0 };
+        self.buffer_layout()
+            .and_then(|layout| layout.latest_tick_count(source.memory()))
+            .unwrap_or(0)
+    }
+
+    fn set_connection_state(&mut self, to: ConnectionState) {
+        if to != self.connection_state {
+            self.connection_events.push(ConnectionEvent { from: self.connection_state, to });
+            self.connection_state = to;
         }
     }
 
+    fn header(&self) -> Option<Header<'_>> {
+        self.source.as_ref().and_then(|source| Header::parse(source.memory()).ok())
+    }
+
+    fn wait_valid_data_event(&self) -> bool {
+        self.source.as_ref().is_none_or(|source| source.wait_for_data(32))
+    }
+
 
-    fn var_headers(&mut self) -> Vec<VarHeader> {
+    fn var_headers(&mut self) -> Vec<VarHeader<'static>> {
         if self.var_headers.is_none() {
-            if let Some(header) = &self.header {
-                if let Some(shared_mem) = &self.shared_mem {
-                    let mut headers = Vec::new();
-                    for i in 0..header.num_vars {
-                        let offset = header.var_header_offset as usize + (i as usize * 144);
-                        let irsdk_struct = IRSDKStruct::new(shared_mem.clone(), offset);
-                        headers.push(VarHeader::from_struct(&irsdk_struct, 0));
-                    }
-                    self.var_headers = Some(headers);
-                }
-            }
+            self.var_headers = self.header().map(|header| header.var_headers().map(VarHeader::into_owned).collect());
         }
         self.var_headers.clone().unwrap_or_default()
     }
 
-    fn var_headers_dict(&mut self) -> HashMap<String, VarHeader> {
+    fn var_headers_dict(&mut self) -> Arc<HashMap<String, VarHeader<'static>>> {
         if self.var_headers_dict.is_none() {
             let mut dict = HashMap::new();
             for var_header in self.var_headers() {
-                dict.insert(var_header.name.clone(), var_header);
+                dict.insert(var_header.name.to_string(), var_header);
             }
-            self.var_headers_dict = Some(dict);
+            self.var_headers_dict = Some(Arc::new(dict));
         }
         self.var_headers_dict.clone().unwrap_or_default()
     }
 
-    fn var_buffer_latest(&mut self) -> Option<VarBuffer> {
-        if let Some(header) = &self.header {
-            let mut var_bufs = header.var_buf.clone();
-            var_bufs.sort_by_key(|v| v.tick_count);
-            var_bufs.reverse();
-            var_bufs.get(1).cloned()
-        } else {
-            None
+    fn latest_data(&self) -> Option<&[u8]> {
+        match &self.var_buffer_latest {
+            Some(frame) => Some(frame.data()),
+            None => self.buffer_layout()?.second_newest(self.source.as_ref()?.memory()),
         }
     }
 
-    fn get_session_info(&mut self, key: &str) -> Option<serde_yaml::Value> {
-        if let Some(header) = &self.header {
-            if self.last_session_info_update < header.session_info_update {
-                self.last_session_info_update = header.session_info_update;
-                for session_data in self.session_info_dict.values_mut() {
-                    if session_data.data.is_some() {
-                        session_data.data_last = session_data.data.clone();
-                    }
-                    session_data.data = None;
-                }
-            }
+    /// The layout validated at startup, or a freshly validated one if the
+    /// memory changed since; `refresh_buffer_layout` keeps the cache current.
+    fn buffer_layout(&self) -> Option<BufferLayout> {
+        let mem = self.source.as_ref()?.memory();
+        match self.buffer_layout {
+            Some(layout) if layout.matches(mem) => Some(layout),
+            _ => self.header().map(|header| BufferLayout::new(&header)),
         }
+    }
 
-        let entry = self.session_info_dict.entry(key.to_string()).or_insert_with(SessionData::new);
-        if entry.data.is_some() {
-            return entry.data.clone();
+    fn refresh_buffer_layout(&mut self) {
+        let layout = self.buffer_layout();
+        if layout.is_some() {
+            self.buffer_layout = layout;
         }
+    }
 
-        if self.parse_yaml_async {
-            if entry.async_session_info_update.unwrap_or(0) < self.last_session_info_update {
-                entry.async_session_info_update = Some(self.last_session_info_update);
-                let key_clone = key.to_string();
-                let mut entry_clone = entry.clone();
-                thread::spawn(move || {
-                    Self::parse_yaml(&key_clone, &mut entry_clone);
-                });
-            }
-        } else {
-            Self::parse_yaml(key, entry);
-        }
-        entry.data.clone()
-    }
-
-    fn get_session_info_binary(&self, key: &str) -> Option<Vec<u8>> {
-        if let Some(header) = &self.header {
-            if let Some(shared_mem) = &self.shared_mem {
-                let start = header.session_info_offset as usize;
-                let end = start + header.session_info_len as usize;
-                let search_str = format!("\n{}:\n", key);
-                let search_bytes = search_str.as_bytes();
-                if let Some(pos) = shared_mem[start..end].windows(search_bytes.len()).position(|window| window == search_bytes) {
-                    let match_start = start + pos
-                    let search_end = b"\n\n";
-                    if let Some(end_pos) = shared_mem[match_start + 1..end].windows(search_end.len()).position(|window| window == search_end) {
-                        let match_end = match_start + 1 + end_pos;
-                        return Some(shared_mem[match_start + 1..match_end].to_vec());
-                    }
-                }
-            }
-        }
-        None
+    /// One top-level section of the session info, e.g. `WeekendInfo`.
+    pub fn get_session_info(&mut self, key: &str) -> Option<serde_yaml::Value> {
+        self.refresh_session_info();
+        self.session_info_parsed.as_ref()?.value.get(key).cloned()
     }
 
-    fn parse_yaml(key: &str, session_data: &mut SessionData) {
-        let binary_data = self.get_session_info_binary(key);
+    pub fn session_info(&mut self) -> Option<&SessionDocument> {
+        self.refresh_session_info();
+        self.session_info_parsed.as_ref()?.document.as_ref()
+    }
 
-        if binary_data.is_none() {
-            if session_data.data_last.is_some() {
-                session_data.data = session_data.data_last.clone();
-            }
-            return;
-        }
+    pub fn session_query(&mut self, query: &str) -> Result<Option<&serde_yaml::Value>, QueryError> {
+        let query = SessionQuery::parse(query)?;
+        self.refresh_session_info();
+        Ok(self.session_info_parsed.as_ref().and_then(|parsed| query.eval(&parsed.value)))
+    }
+
+    /// The newest fully parsed session info. With `parse_yaml_async` this
+    /// never blocks: it keeps returning the previous version until the
+    /// worker has parsed the new one.
+    pub fn parsed_session_info(&mut self) -> Option<Arc<ParsedSessionInfo>> {
+        self.refresh_session_info();
+        self.session_info_parsed.clone()
+    }
+
+    /// The current car setup with its values parsed; see `CarSetupTree`.
+    pub fn car_setup(&mut self) -> Option<CarSetupTree> {
+        self.parsed_session_info()?.car_setup()
+    }
+
+    /// What changed at each `session_info_update` since the last call. Bumps
+    /// are picked up by `update_connection` (and so the frame iterators) as
+    /// well as by the session info accessors. The first version parsed after
+    /// startup is not diffed against anything.
+    pub fn take_session_info_events(&mut self) -> Vec<SessionInfoEvent> {
+        self.refresh_session_info();
+        std::mem::take(&mut self.session_info_events)
+    }
+
+    /// Why the newest session info string could not be parsed. The previous
+    /// version keeps being served until a newer one parses.
+    pub fn session_info_error(&mut self) -> Option<&serde_yaml::Error> {
+        self.refresh_session_info();
+        self.session_info_error.as_ref().map(|(_, e)| e)
+    }
 
-        let binary_data = binary_data.unwrap();
+    /// What the sanitizer had to repair in the current session info.
+    pub fn session_info_fixes(&mut self) -> &[YamlFix] {
+        self.refresh_session_info();
+        self.session_info_parsed.as_ref().map_or(&[], |parsed| &parsed.fixes)
+    }
 
-        if session_data.data_binary.as_ref() == Some(&binary_data) && session_data.data_last.is_some() {
-            session_data.data = session_data.data_last.clone();
+    /// Starts keeping every distinct session info string; see `session_archive`.
+    pub fn enable_session_archive(&mut self) {
+        self.session_archive.get_or_insert_with(SessionArchive::new);
+    }
+
+    /// Continues recording into an archive, e.g. one loaded from disk.
+    pub fn set_session_archive(&mut self, archive: Option<SessionArchive>) {
+        self.session_archive = archive;
+    }
+
+    /// The archive, with the current session info recorded. Versions are
+    /// recorded by `update_connection` (and so the frame iterators) and by
+    /// the session info accessors. The archive survives reconnects.
+    pub fn session_archive(&mut self) -> Option<&SessionArchive> {
+        self.refresh_session_info();
+        self.session_archive.as_ref()
+    }
+
+    pub fn take_session_archive(&mut self) -> Option<SessionArchive> {
+        self.refresh_session_info();
+        self.session_archive.take()
+    }
+
+    fn archive_session_info(&mut self, update: i32) {
+        if self.session_archive.is_none() {
             return;
         }
-        session_data.data_binary = Some(binary_data.clone());
+        if self.archive_handles.is_none() {
+            self.archive_handles = Some((self.handle("SessionNum").ok(), self.handle("SessionTime").ok()));
+        }
+        let (session_num, session_time) = self.archive_handles.unwrap_or_default();
+        let session_num = session_num.and_then(|handle| self.read(handle));
+        match &self.session_archive {
+            Some(archive) if archive.last_update() != Some(update) || archive.last_session_num() != session_num => {}
+            _ => return,
+        }
+        let session_time = session_time.and_then(|handle| self.read(handle));
+        let Some(binary_data) = self.session_info_bytes() else {
+            return;
+        };
+        let yaml = decode_cp1252(binary_data).split('\0').next().unwrap_or_default().to_string();
+        if let Some(archive) = &mut self.session_archive {
+            archive.record(update, session_num, session_time, &yaml);
+        }
+    }
 
-        let yaml_src = str::from_utf8(&binary_data)
-            .unwrap_or("")
-            .replace(|c: char| !c.is_ascii_printable(), "")
-            .trim_end_matches('\0');
+    fn refresh_session_info(&mut self) {
+        let update = self.session_info_update();
+        self.archive_session_info(update);
+        if self.parse_yaml_async {
+            self.receive_session_info();
+            self.request_session_info(update);
+            return;
+        }
+        if self.session_info_parsed.as_ref().is_some_and(|parsed| parsed.update >= update) {
+            return;
+        }
+        // A string that failed once fails again; wait for the sim to replace it.
+        if self.session_info_error.as_ref().is_some_and(|(failed, _)| *failed == update) {
+            return;
+        }
+        let Some(binary_data) = self.session_info_bytes() else {
+            return;
+        };
+        match ParsedSessionInfo::parse(update, binary_data) {
+            Ok(parsed) => self.publish_session_info(parsed),
+            Err(e) => self.session_info_error = Some((update, e)),
+        }
+    }
 
-        let yaml_src = if key == "DriverInfo" {
-            let re = Regex::new(r"(DriverSetupName|UserName|TeamName|AbbrevName|Initials): (.*)").unwrap();
-            re.replace_all(&yaml_src, |caps: &regex::Captures| {
-                format!("{} \"{}\"", &caps[1], &caps[2].replace('"', "\\\"").replace('\\', "\\\\"))
-            }).to_string()
-        } else {
-            yaml_src.to_string()
+    fn request_session_info(&mut self, update: i32) {
+        if self.session_info_requested_update == Some(update) {
+            return;
+        }
+        let Some(binary_data) = self.session_info_bytes() else {
+            return;
         };
+        let binary_data = binary_data.to_vec();
+        self.session_worker.get_or_insert_with(SessionWorker::spawn).parse(update, binary_data);
+        self.session_info_requested_update = Some(update);
+    }
 
-        let yaml_src = Regex::new(r"(\w+: )(,.*)").unwrap()
-            .replace_all(&yaml_src, |caps: &regex::Captures| {
-                format!("{} \"{}\"", &caps[1], &caps[2])
-            }).to_string();
-
-        match serde_yaml::from_str(&yaml_src) {
-            Ok(result) => {
-                if let Some(map) = result.as_mapping() {
-                    if let Some(value) = map.get(key) {
-                        session_data.data = Some(value.clone());
-                        if session_data.data.is_some() {
-                            session_data.update = Some(self.last_session_info_update);
-                        } else if session_data.data_last.is_some() {
-                            session_data.data = session_data.data_last.clone();
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                println!("YAML parse error: {}", e);
-                if session_data.data_last.is_some() {
-                    session_data.data = session_data.data_last.clone();
-                }
+    fn receive_session_info(&mut self) {
+        let Some(worker) = &self.session_worker else {
+            return;
+        };
+        let results: Vec<_> = worker.results().collect();
+        for (update, result) in results {
+            match result {
+                Ok(parsed) => self.publish_session_info(parsed),
+                Err(e) => self.session_info_error = Some((update, e)),
             }
         }
     }
 
+    fn publish_session_info(&mut self, parsed: ParsedSessionInfo) {
+        if let Some(previous) = &self.session_info_parsed {
+            let default = SessionDocument::default();
+            let changes = diff_session_info(
+                previous.document.as_ref().unwrap_or(&default),
+                parsed.document.as_ref().unwrap_or(&default),
+            );
+            self.session_info_events.push(SessionInfoEvent {
+                from_update: previous.update,
+                to_update: parsed.update,
+                changes,
+            });
+        }
+        self.session_info_parsed = Some(Arc::new(parsed));
+        self.session_info_error = None;
+    }
+
+    fn session_info_bytes(&self) -> Option<&[u8]> {
+        self.header()?.session_info()
+    }
+
+    #[cfg(windows)]
     fn broadcast_msg_id(&mut self) -> u32 {
         if self.broadcast_msg_id.is_none() {
-            let msg_name = format!("{}\0", BROADCAST_MSG_NAME);
-            let msg_name_w: Vec<u16> = msg_name.encode_utf16().chain(std::iter::once(0)).collect();
+            let msg_name_w: Vec<u16> = BROADCAST_MSG_NAME.encode_utf16().chain(std::iter::once(0)).collect();
             self.broadcast_msg_id = unsafe {
                 Some(RegisterWindowMessageW(PCWSTR(msg_name_w.as_ptr())))
             };
@@ -575,18 +787,24 @@ impl IRSDK {
         self.broadcast_msg_id.unwrap()
     }
 
+    #[cfg(windows)]
     fn broadcast_msg(&mut self, broadcast_type: u32, var1: i32, var2: i32, var3: i32) -> bool {
         let msg_id = self.broadcast_msg_id();
         unsafe {
             SendNotifyMessageW(
                 HWND_BROADCAST,
                 msg_id,
-                (broadcast_type as u32 | (var1 as u32) << 16) as u32,
-                (var2 as u32 | (var3 as u32) << 16) as u32
+                WPARAM((broadcast_type | (var1 as u32) << 16) as usize),
+                LPARAM((var2 as u32 | (var3 as u32) << 16) as isize)
             ).as_bool()
         }
     }
 
+    #[cfg(not(windows))]
+    fn broadcast_msg(&mut self, _broadcast_type: u32, _var1: i32, _var2: i32, _var3: i32) -> bool {
+        false
+    }
+
     fn pad_car_num(&self, num: &str) -> i32 {
         let num: i32 = num.parse().unwrap_or(0);
         let num_str = num.to_string();
@@ -2,8 +2,47 @@ pub mod constants;
 pub mod structs;
 pub mod irsdk;
 pub mod ibt;
+pub mod source;
+pub mod value;
+pub mod session;
+pub mod query;
+pub mod fake_sim;
+pub mod connection;
+pub mod frame;
+pub mod handle;
+pub mod bitfields;
+pub mod enums;
+pub mod units;
+pub mod time;
+pub mod telemetry;
+pub mod codegen;
+pub mod sanitize;
+mod session_worker;
+pub mod session_diff;
+pub mod session_archive;
+pub mod setup;
 
 pub use constants::*;
 pub use structs::*;
 pub use irsdk::IRSDK;
 pub use ibt::IBT;
+pub use source::{DefaultSource, FileSource, TelemetrySource};
+#[cfg(windows)]
+pub use source::WindowsSource;
+pub use value::{FromVarValue, VarError, VarType, VarValue};
+pub use session::{ParsedSessionInfo, SessionDocument};
+pub use sanitize::{SanitizedYaml, YamlFix};
+pub use session_diff::{SessionInfoChange, SessionInfoEvent};
+pub use session_archive::{ArchiveError, ArchivedSessionInfo, SessionArchive};
+pub use setup::{CarSetupTree, SetupNode, SetupSection, SetupValue};
+pub use query::{QueryError, SessionQuery};
+pub use fake_sim::FakeSim;
+pub use connection::{ConnectionEvent, ConnectionState};
+pub use frame::{Frame, FrameStream, Frames};
+pub use handle::{VarHandle, VarScalar, VarSlice};
+pub use bitfields::{CameraState, EngineWarnings, PaceFlags, PitSvFlags, SessionFlags};
+pub use enums::{CarLeftRight, PaceMode, PitSvStatus, SessionState, TrackWetness, TrkLoc, TrkSurf};
+pub use units::{Quantity, Unit};
+pub use time::SessionTime;
+pub use telemetry::{Telemetry, TelemetryField};
+pub use irsdk_derive::Telemetry;
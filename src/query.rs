@@ -0,0 +1,150 @@
+use std::fmt;
+use std::str::FromStr;
+use serde_yaml::Value;
+
+#[derive(Debug)]
+pub enum QueryError {
+    Empty,
+    Syntax(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryError::Empty => write!(f, "empty session query"),
+            QueryError::Syntax(message) => write!(f, "invalid session query: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryStep {
+    Key(String),
+    Select { key: String, value: String },
+}
+
+/// A session string lookup in the official SDK syntax, e.g.
+/// `SessionInfo:Sessions:SessionNum:{2}ResultsPositions:Position:{1}CarIdx:`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionQuery {
+    steps: Vec<QueryStep>,
+}
+
+impl SessionQuery {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut steps = Vec::new();
+        let mut rest = query.trim();
+        while !rest.is_empty() {
+            let colon = rest.find(':').unwrap_or(rest.len());
+            let key = &rest[..colon];
+            if key.is_empty() || key.contains(['{', '}']) {
+                return Err(QueryError::Syntax(format!("invalid key at '{}'", rest)));
+            }
+            rest = rest.get(colon + 1..).unwrap_or("");
+
+            if let Some(selector) = rest.strip_prefix('{') {
+                let close = selector
+                    .find('}')
+                    .ok_or_else(|| QueryError::Syntax(format!("unterminated selector after '{}'", key)))?;
+                steps.push(QueryStep::Select {
+                    key: key.to_string(),
+                    value: selector[..close].to_string(),
+                });
+                rest = &selector[close + 1..];
+            } else {
+                steps.push(QueryStep::Key(key.to_string()));
+            }
+        }
+        if steps.is_empty() {
+            return Err(QueryError::Empty);
+        }
+        Ok(SessionQuery { steps })
+    }
+
+    pub fn steps(&self) -> &[QueryStep] {
+        &self.steps
+    }
+
+    pub fn eval<'a>(&self, root: &'a Value) -> Option<&'a Value> {
+        let mut current = root;
+        for step in &self.steps {
+            current = match step {
+                QueryStep::Key(key) => current.as_mapping()?.get(key.as_str())?,
+                QueryStep::Select { key, value } => current
+                    .as_sequence()?
+                    .iter()
+                    .find(|item| item.get(key.as_str()).and_then(scalar_to_string).as_deref() == Some(value.as_str()))?,
+            };
+        }
+        Some(current)
+    }
+}
+
+impl FromStr for SessionQuery {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        SessionQuery::parse(query)
+    }
+}
+
+impl fmt::Display for SessionQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            match step {
+                QueryStep::Key(key) => write!(f, "{}:", key)?,
+                QueryStep::Select { key, value } => write!(f, "{}:{{{}}}", key, value)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_INFO: &str = "
+SessionInfo:
+ Sessions:
+ - SessionNum: 0
+   ResultsPositions:
+ - SessionNum: 2
+   ResultsPositions:
+   - Position: 1
+     CarIdx: 7
+   - Position: 2
+     CarIdx: 3
+";
+
+    #[test]
+    fn parses_sdk_syntax() {
+        let query = SessionQuery::parse("SessionInfo:Sessions:SessionNum:{2}ResultsPositions:Position:{1}CarIdx:").unwrap();
+        assert_eq!(query.steps().len(), 6);
+        assert_eq!(query.steps()[2], QueryStep::Select { key: "SessionNum".to_string(), value: "2".to_string() });
+        assert_eq!(query.to_string(), "SessionInfo:Sessions:SessionNum:{2}ResultsPositions:Position:{1}CarIdx:");
+        assert!(matches!(SessionQuery::parse("  "), Err(QueryError::Empty)));
+        assert!(matches!(SessionQuery::parse("Drivers:CarIdx:{1"), Err(QueryError::Syntax(_))));
+        assert!(matches!(SessionQuery::parse("::"), Err(QueryError::Syntax(_))));
+    }
+
+    #[test]
+    fn selects_list_items() {
+        let value: Value = serde_yaml::from_str(SESSION_INFO).unwrap();
+        let car_idx = |query: &str| SessionQuery::parse(query).unwrap().eval(&value).and_then(scalar_to_string);
+        assert_eq!(car_idx("SessionInfo:Sessions:SessionNum:{2}ResultsPositions:Position:{2}CarIdx:").as_deref(), Some("3"));
+        assert_eq!(car_idx("SessionInfo:Sessions:SessionNum:{0}ResultsPositions:Position:{1}CarIdx:"), None);
+        assert_eq!(car_idx("SessionInfo:Sessions:SessionNum:{5}ResultsPositions:"), None);
+    }
+}
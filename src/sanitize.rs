@@ -0,0 +1,123 @@
+use std::fmt;
+use std::sync::LazyLock;
+use regex::Regex;
+use crate::structs::decode_cp1252;
+
+// Free text the sim writes unquoted; quoted whenever YAML would not read the
+// value back as the same string.
+const FREE_TEXT_KEYS: &[&str] = &["UserName", "TeamName", "AbbrevName", "Initials", "DriverSetupName", "ClubName", "DivisionName"];
+
+// Characters that cannot start a plain YAML scalar.
+const INDICATORS: &[char] = &[',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@', '`'];
+
+// `key: value`, optionally as the first line of a list item.
+static KEY_VALUE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(\s*(?:-\s+)?)(\w+): (.*)$").unwrap());
+
+/// One change `sanitize_yaml` made to make the session info parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum YamlFix {
+    /// NUL padding after the document was cut off.
+    TrailingNuls,
+    /// Tabs, which YAML does not allow for indentation, replaced by spaces.
+    Tabs { count: usize },
+    /// Other control characters, including `\r`, removed.
+    ControlChars { count: usize },
+    /// The value of `key` on `line` (1-based) was wrapped in double quotes.
+    QuotedValue { line: usize, key: String },
+}
+
+impl fmt::Display for YamlFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YamlFix::TrailingNuls => write!(f, "removed trailing NULs"),
+            YamlFix::Tabs { count } => write!(f, "replaced {} tabs", count),
+            YamlFix::ControlChars { count } => write!(f, "removed {} control characters", count),
+            YamlFix::QuotedValue { line, key } => write!(f, "quoted {} on line {}", key, line),
+        }
+    }
+}
+
+/// Session info decoded and cleaned up for `serde_yaml`, with what was fixed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SanitizedYaml {
+    pub yaml: String,
+    pub fixes: Vec<YamlFix>,
+}
+
+/// Decodes raw session info and repairs the quirks the sim is known to write:
+/// NUL padding, tabs, stray control characters, and unquoted names or setup
+/// names containing `:`, `#`, quotes or a leading `,`.
+pub fn sanitize_yaml(binary_data: &[u8]) -> SanitizedYaml {
+    sanitize_yaml_str(&decode_cp1252(binary_data))
+}
+
+/// `sanitize_yaml` for session info that is already decoded.
+pub fn sanitize_yaml_str(decoded: &str) -> SanitizedYaml {
+    let mut fixes = Vec::new();
+    let text = match decoded.find('\0') {
+        Some(end) => {
+            fixes.push(YamlFix::TrailingNuls);
+            &decoded[..end]
+        }
+        None => decoded,
+    };
+
+    let mut tabs = 0;
+    let mut control_chars = 0;
+    let text: String = text
+        .chars()
+        .filter_map(|c| match c {
+            '\n' => Some(c),
+            '\t' => {
+                tabs += 1;
+                Some(' ')
+            }
+            c if c.is_control() => {
+                control_chars += 1;
+                None
+            }
+            c => Some(c),
+        })
+        .collect();
+    if tabs > 0 {
+        fixes.push(YamlFix::Tabs { count: tabs });
+    }
+    if control_chars > 0 {
+        fixes.push(YamlFix::ControlChars { count: control_chars });
+    }
+
+    let mut yaml = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            yaml.push('\n');
+        }
+        match KEY_VALUE.captures(line) {
+            Some(caps) if needs_quotes(&caps[2], caps[3].trim_end()) => {
+                let value = caps[3].trim_end().replace('\\', "\\\\").replace('"', "\\\"");
+                yaml.push_str(&format!("{}{}: \"{}\"", &caps[1], &caps[2], value));
+                fixes.push(YamlFix::QuotedValue { line: i + 1, key: caps[2].to_string() });
+            }
+            _ => yaml.push_str(line),
+        }
+    }
+    SanitizedYaml { yaml, fixes }
+}
+
+fn needs_quotes(key: &str, value: &str) -> bool {
+    if value.is_empty() || is_quoted(value) {
+        return false;
+    }
+    if FREE_TEXT_KEYS.contains(&key) {
+        return !matches!(serde_yaml::from_str(value), Ok(serde_yaml::Value::String(s)) if s == value);
+    }
+    value.starts_with(INDICATORS)
+        || value.starts_with("- ")
+        || value.starts_with("? ")
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+}
+
+fn is_quoted(value: &str) -> bool {
+    value.len() >= 2 && value.starts_with('"') && value.ends_with('"') && !value[1..value.len() - 1].contains('"')
+}
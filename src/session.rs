@@ -0,0 +1,374 @@
+use std::collections::BTreeMap;
+use serde::de::{DeserializeOwned, Deserializer};
+use serde::{Deserialize, Serialize};
+use crate::sanitize::{sanitize_yaml, sanitize_yaml_str, SanitizedYaml, YamlFix};
+use crate::setup::CarSetupTree;
+
+fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_yaml::Value::deserialize(deserializer)?;
+    if let Ok(parsed) = T::deserialize(value.clone()) {
+        return Ok(Some(parsed));
+    }
+    let scalar = match value {
+        serde_yaml::Value::Bool(b) => b.to_string(),
+        serde_yaml::Value::Number(n) => n.to_string(),
+        _ => return Ok(None),
+    };
+    Ok(T::deserialize(serde_yaml::Value::String(scalar)).ok())
+}
+
+macro_rules! session_section {
+    ($name:ident { $($field:ident: $ty:ty = $yaml:literal,)* }) => {
+        #[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+        pub struct $name {
+            $(
+                #[serde(rename = $yaml, default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$ty>,
+            )*
+        }
+    };
+}
+
+session_section!(SessionDocument {
+    weekend_info: WeekendInfo = "WeekendInfo",
+    session_info: SessionInfo = "SessionInfo",
+    qualify_results_info: QualifyResultsInfo = "QualifyResultsInfo",
+    camera_info: CameraInfo = "CameraInfo",
+    radio_info: RadioInfo = "RadioInfo",
+    driver_info: DriverInfo = "DriverInfo",
+    split_time_info: SplitTimeInfo = "SplitTimeInfo",
+    car_setup: CarSetup = "CarSetup",
+});
+
+session_section!(WeekendInfo {
+    track_name: String = "TrackName",
+    track_id: i64 = "TrackID",
+    track_length: String = "TrackLength",
+    track_length_official: String = "TrackLengthOfficial",
+    track_display_name: String = "TrackDisplayName",
+    track_display_short_name: String = "TrackDisplayShortName",
+    track_config_name: String = "TrackConfigName",
+    track_city: String = "TrackCity",
+    track_country: String = "TrackCountry",
+    track_altitude: String = "TrackAltitude",
+    track_latitude: String = "TrackLatitude",
+    track_longitude: String = "TrackLongitude",
+    track_north_offset: String = "TrackNorthOffset",
+    track_num_turns: i64 = "TrackNumTurns",
+    track_pit_speed_limit: String = "TrackPitSpeedLimit",
+    track_type: String = "TrackType",
+    track_direction: String = "TrackDirection",
+    track_weather_type: String = "TrackWeatherType",
+    track_skies: String = "TrackSkies",
+    track_surface_temp: String = "TrackSurfaceTemp",
+    track_air_temp: String = "TrackAirTemp",
+    track_air_pressure: String = "TrackAirPressure",
+    track_wind_vel: String = "TrackWindVel",
+    track_wind_dir: String = "TrackWindDir",
+    track_relative_humidity: String = "TrackRelativeHumidity",
+    track_fog_level: String = "TrackFogLevel",
+    track_precipitation: String = "TrackPrecipitation",
+    track_cleanup: i64 = "TrackCleanup",
+    track_dynamic_track: i64 = "TrackDynamicTrack",
+    track_version: String = "TrackVersion",
+    series_id: i64 = "SeriesID",
+    season_id: i64 = "SeasonID",
+    session_id: i64 = "SessionID",
+    sub_session_id: i64 = "SubSessionID",
+    league_id: i64 = "LeagueID",
+    official: i64 = "Official",
+    race_week: i64 = "RaceWeek",
+    event_type: String = "EventType",
+    category: String = "Category",
+    sim_mode: String = "SimMode",
+    team_racing: i64 = "TeamRacing",
+    min_drivers: i64 = "MinDrivers",
+    max_drivers: i64 = "MaxDrivers",
+    dc_rule_set: String = "DCRuleSet",
+    qualifier_must_start_race: i64 = "QualifierMustStartRace",
+    num_car_classes: i64 = "NumCarClasses",
+    num_car_types: i64 = "NumCarTypes",
+    heat_racing: i64 = "HeatRacing",
+    build_type: String = "BuildType",
+    build_target: String = "BuildTarget",
+    build_version: String = "BuildVersion",
+    weekend_options: WeekendOptions = "WeekendOptions",
+    telemetry_options: TelemetryOptions = "TelemetryOptions",
+});
+
+session_section!(WeekendOptions {
+    num_starters: i64 = "NumStarters",
+    starting_grid: String = "StartingGrid",
+    qualify_scoring: String = "QualifyScoring",
+    course_cautions: String = "CourseCautions",
+    standing_start: i64 = "StandingStart",
+    short_parade_lap: i64 = "ShortParadeLap",
+    restarts: String = "Restarts",
+    weather_type: String = "WeatherType",
+    skies: String = "Skies",
+    wind_direction: String = "WindDirection",
+    wind_speed: String = "WindSpeed",
+    weather_temp: String = "WeatherTemp",
+    relative_humidity: String = "RelativeHumidity",
+    fog_level: String = "FogLevel",
+    time_of_day: String = "TimeOfDay",
+    date: String = "Date",
+    earth_rotation_speedup_factor: i64 = "EarthRotationSpeedupFactor",
+    unofficial: i64 = "Unofficial",
+    commercial_mode: String = "CommercialMode",
+    night_mode: String = "NightMode",
+    is_fixed_setup: i64 = "IsFixedSetup",
+    strict_laps_checking: String = "StrictLapsChecking",
+    has_open_registration: i64 = "HasOpenRegistration",
+    hardcore_level: i64 = "HardcoreLevel",
+    num_joker_laps: i64 = "NumJokerLaps",
+    incident_limit: String = "IncidentLimit",
+    fast_repairs_limit: String = "FastRepairsLimit",
+    green_white_checkered_limit: i64 = "GreenWhiteCheckeredLimit",
+});
+
+session_section!(TelemetryOptions {
+    telemetry_disk_file: String = "TelemetryDiskFile",
+});
+
+session_section!(SessionInfo {
+    current_session_num: i64 = "CurrentSessionNum",
+    sessions: Vec<Session> = "Sessions",
+});
+
+session_section!(Session {
+    session_num: i64 = "SessionNum",
+    session_laps: String = "SessionLaps",
+    session_time: String = "SessionTime",
+    session_num_laps_to_avg: i64 = "SessionNumLapsToAvg",
+    session_type: String = "SessionType",
+    session_track_rubber_state: String = "SessionTrackRubberState",
+    session_name: String = "SessionName",
+    session_sub_type: String = "SessionSubType",
+    session_skipped: i64 = "SessionSkipped",
+    session_run_groups_used: i64 = "SessionRunGroupsUsed",
+    session_enforce_tire_compound_change: i64 = "SessionEnforceTireCompoundChange",
+    results_positions: Vec<ResultsPosition> = "ResultsPositions",
+    results_fastest_lap: Vec<ResultsFastestLap> = "ResultsFastestLap",
+    results_average_lap_time: f64 = "ResultsAverageLapTime",
+    results_num_caution_flags: i64 = "ResultsNumCautionFlags",
+    results_num_caution_laps: i64 = "ResultsNumCautionLaps",
+    results_num_lead_changes: i64 = "ResultsNumLeadChanges",
+    results_laps_complete: i64 = "ResultsLapsComplete",
+    results_official: i64 = "ResultsOfficial",
+});
+
+session_section!(ResultsPosition {
+    position: i64 = "Position",
+    class_position: i64 = "ClassPosition",
+    car_idx: i64 = "CarIdx",
+    lap: i64 = "Lap",
+    time: f64 = "Time",
+    fastest_lap: i64 = "FastestLap",
+    fastest_time: f64 = "FastestTime",
+    last_time: f64 = "LastTime",
+    laps_led: i64 = "LapsLed",
+    laps_complete: i64 = "LapsComplete",
+    joker_laps_complete: i64 = "JokerLapsComplete",
+    laps_driven: f64 = "LapsDriven",
+    incidents: i64 = "Incidents",
+    reason_out_id: i64 = "ReasonOutId",
+    reason_out_str: String = "ReasonOutStr",
+});
+
+session_section!(ResultsFastestLap {
+    car_idx: i64 = "CarIdx",
+    fastest_lap: i64 = "FastestLap",
+    fastest_time: f64 = "FastestTime",
+});
+
+session_section!(QualifyResultsInfo {
+    results: Vec<QualifyResult> = "Results",
+});
+
+session_section!(QualifyResult {
+    position: i64 = "Position",
+    class_position: i64 = "ClassPosition",
+    car_idx: i64 = "CarIdx",
+    fastest_lap: i64 = "FastestLap",
+    fastest_time: f64 = "FastestTime",
+});
+
+session_section!(CameraInfo {
+    groups: Vec<CameraGroup> = "Groups",
+});
+
+session_section!(CameraGroup {
+    group_num: i64 = "GroupNum",
+    group_name: String = "GroupName",
+    is_scenic: bool = "IsScenic",
+    cameras: Vec<Camera> = "Cameras",
+});
+
+session_section!(Camera {
+    camera_num: i64 = "CameraNum",
+    camera_name: String = "CameraName",
+});
+
+session_section!(RadioInfo {
+    selected_radio_num: i64 = "SelectedRadioNum",
+    radios: Vec<Radio> = "Radios",
+});
+
+session_section!(Radio {
+    radio_num: i64 = "RadioNum",
+    hop_count: i64 = "HopCount",
+    num_frequencies: i64 = "NumFrequencies",
+    tuned_to_frequency_num: i64 = "TunedToFrequencyNum",
+    scanning_is_on: i64 = "ScanningIsOn",
+    frequencies: Vec<Frequency> = "Frequencies",
+});
+
+session_section!(Frequency {
+    frequency_num: i64 = "FrequencyNum",
+    frequency_name: String = "FrequencyName",
+    priority: i64 = "Priority",
+    car_idx: i64 = "CarIdx",
+    entry_idx: i64 = "EntryIdx",
+    club_id: i64 = "ClubID",
+    can_scan: i64 = "CanScan",
+    can_squawk: i64 = "CanSquawk",
+    muted: i64 = "Muted",
+    is_mutable: i64 = "IsMutable",
+    is_deletable: i64 = "IsDeletable",
+});
+
+session_section!(DriverInfo {
+    driver_car_idx: i64 = "DriverCarIdx",
+    driver_user_id: i64 = "DriverUserID",
+    pace_car_idx: i64 = "PaceCarIdx",
+    driver_head_pos_x: f64 = "DriverHeadPosX",
+    driver_head_pos_y: f64 = "DriverHeadPosY",
+    driver_head_pos_z: f64 = "DriverHeadPosZ",
+    driver_is_admin: i64 = "DriverIsAdmin",
+    driver_car_idle_rpm: f64 = "DriverCarIdleRPM",
+    driver_car_red_line: f64 = "DriverCarRedLine",
+    driver_car_eng_cylinder_count: i64 = "DriverCarEngCylinderCount",
+    driver_car_fuel_kg_per_ltr: f64 = "DriverCarFuelKgPerLtr",
+    driver_car_fuel_max_ltr: f64 = "DriverCarFuelMaxLtr",
+    driver_car_max_fuel_pct: f64 = "DriverCarMaxFuelPct",
+    driver_car_gear_num_forward: i64 = "DriverCarGearNumForward",
+    driver_car_gear_neutral: i64 = "DriverCarGearNeutral",
+    driver_car_gear_reverse: i64 = "DriverCarGearReverse",
+    driver_car_sl_first_rpm: f64 = "DriverCarSLFirstRPM",
+    driver_car_sl_shift_rpm: f64 = "DriverCarSLShiftRPM",
+    driver_car_sl_last_rpm: f64 = "DriverCarSLLastRPM",
+    driver_car_sl_blink_rpm: f64 = "DriverCarSLBlinkRPM",
+    driver_car_version: String = "DriverCarVersion",
+    driver_pit_trk_pct: f64 = "DriverPitTrkPct",
+    driver_car_est_lap_time: f64 = "DriverCarEstLapTime",
+    driver_setup_name: String = "DriverSetupName",
+    driver_setup_is_modified: i64 = "DriverSetupIsModified",
+    driver_setup_load_type_name: String = "DriverSetupLoadTypeName",
+    driver_setup_passed_tech: i64 = "DriverSetupPassedTech",
+    driver_incident_count: i64 = "DriverIncidentCount",
+    drivers: Vec<Driver> = "Drivers",
+});
+
+session_section!(Driver {
+    car_idx: i64 = "CarIdx",
+    user_name: String = "UserName",
+    abbrev_name: String = "AbbrevName",
+    initials: String = "Initials",
+    user_id: i64 = "UserID",
+    team_id: i64 = "TeamID",
+    team_name: String = "TeamName",
+    car_number: String = "CarNumber",
+    car_number_raw: i64 = "CarNumberRaw",
+    car_path: String = "CarPath",
+    car_class_id: i64 = "CarClassID",
+    car_id: i64 = "CarID",
+    car_is_pace_car: i64 = "CarIsPaceCar",
+    car_is_ai: i64 = "CarIsAI",
+    car_is_electric: i64 = "CarIsElectric",
+    car_screen_name: String = "CarScreenName",
+    car_screen_name_short: String = "CarScreenNameShort",
+    car_class_short_name: String = "CarClassShortName",
+    car_class_rel_speed: i64 = "CarClassRelSpeed",
+    car_class_license_level: i64 = "CarClassLicenseLevel",
+    car_class_max_fuel_pct: String = "CarClassMaxFuelPct",
+    car_class_weight_penalty: String = "CarClassWeightPenalty",
+    car_class_power_adjust: String = "CarClassPowerAdjust",
+    car_class_dry_tire_set_limit: String = "CarClassDryTireSetLimit",
+    car_class_color: i64 = "CarClassColor",
+    car_class_est_lap_time: f64 = "CarClassEstLapTime",
+    i_rating: i64 = "IRating",
+    lic_level: i64 = "LicLevel",
+    lic_sub_level: i64 = "LicSubLevel",
+    lic_string: String = "LicString",
+    lic_color: i64 = "LicColor",
+    is_spectator: i64 = "IsSpectator",
+    car_design_str: String = "CarDesignStr",
+    helmet_design_str: String = "HelmetDesignStr",
+    suit_design_str: String = "SuitDesignStr",
+    body_type: i64 = "BodyType",
+    face_type: i64 = "FaceType",
+    helmet_type: i64 = "HelmetType",
+    car_number_design_str: String = "CarNumberDesignStr",
+    car_sponsor_1: i64 = "CarSponsor_1",
+    car_sponsor_2: i64 = "CarSponsor_2",
+    club_name: String = "ClubName",
+    club_id: i64 = "ClubID",
+    division_name: String = "DivisionName",
+    division_id: i64 = "DivisionID",
+    cur_driver_incident_count: i64 = "CurDriverIncidentCount",
+    team_incident_count: i64 = "TeamIncidentCount",
+});
+
+session_section!(SplitTimeInfo {
+    sectors: Vec<Sector> = "Sectors",
+});
+
+session_section!(Sector {
+    sector_num: i64 = "SectorNum",
+    sector_start_pct: f64 = "SectorStartPct",
+});
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct CarSetup {
+    #[serde(rename = "UpdateCount", default, deserialize_with = "lenient", skip_serializing_if = "Option::is_none")]
+    pub update_count: Option<i64>,
+    #[serde(flatten)]
+    pub sections: BTreeMap<String, serde_yaml::Value>,
+}
+
+/// The whole session info string parsed once, tagged with the
+/// `session_info_update` it was read at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedSessionInfo {
+    pub update: i32,
+    pub value: serde_yaml::Value,
+    pub document: Option<SessionDocument>,
+    pub fixes: Vec<YamlFix>,
+}
+
+impl ParsedSessionInfo {
+    pub fn parse(update: i32, binary_data: &[u8]) -> Result<Self, serde_yaml::Error> {
+        Self::from_sanitized(update, sanitize_yaml(binary_data))
+    }
+
+    /// Parses session info that is already decoded, such as an archived copy.
+    pub fn parse_str(update: i32, yaml: &str) -> Result<Self, serde_yaml::Error> {
+        Self::from_sanitized(update, sanitize_yaml_str(yaml))
+    }
+
+    fn from_sanitized(update: i32, sanitized: SanitizedYaml) -> Result<Self, serde_yaml::Error> {
+        let value: serde_yaml::Value = serde_yaml::from_str(&sanitized.yaml)?;
+        let document = serde_yaml::from_value(value.clone()).ok();
+        Ok(ParsedSessionInfo { update, value, document, fixes: sanitized.fixes })
+    }
+
+    /// The `CarSetup` section as a typed tree, in the order the sim wrote it.
+    pub fn car_setup(&self) -> Option<CarSetupTree> {
+        CarSetupTree::from_session_value(&self.value)
+    }
+}
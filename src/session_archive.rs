@@ -0,0 +1,116 @@
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use serde::{Deserialize, Serialize};
+use crate::session::ParsedSessionInfo;
+
+#[derive(Debug)]
+pub enum ArchiveError {
+    FileAccessError(String),
+    FormatError(String),
+}
+
+impl fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::FileAccessError(message) => write!(f, "cannot access session archive: {}", message),
+            ArchiveError::FormatError(message) => write!(f, "invalid session archive: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+/// One version of the session info string as the sim published it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArchivedSessionInfo {
+    pub update: i32,
+    /// `SessionNum` when the version was recorded, if it could be read.
+    #[serde(default)]
+    pub session_num: Option<i32>,
+    /// `SessionTime` when the version was recorded, if it could be read.
+    #[serde(default)]
+    pub session_time: Option<f64>,
+    pub yaml: String,
+}
+
+impl ArchivedSessionInfo {
+    pub fn parse(&self) -> Result<ParsedSessionInfo, serde_yaml::Error> {
+        ParsedSessionInfo::parse_str(self.update, &self.yaml)
+    }
+}
+
+/// Every distinct session info string seen, oldest first. Saved as YAML.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionArchive {
+    entries: Vec<ArchivedSessionInfo>,
+    #[serde(skip)]
+    last_update: Option<i32>,
+    #[serde(skip)]
+    last_session_num: Option<i32>,
+}
+
+impl SessionArchive {
+    pub fn new() -> Self {
+        SessionArchive::default()
+    }
+
+    pub fn load(path: &str) -> Result<Self, ArchiveError> {
+        let file = File::open(path).map_err(|e| ArchiveError::FileAccessError(e.to_string()))?;
+        let mut archive: SessionArchive =
+            serde_yaml::from_reader(BufReader::new(file)).map_err(|e| ArchiveError::FormatError(e.to_string()))?;
+        archive.last_update = archive.entries.last().map(|entry| entry.update);
+        archive.last_session_num = archive.entries.last().and_then(|entry| entry.session_num);
+        Ok(archive)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), ArchiveError> {
+        let file = File::create(path).map_err(|e| ArchiveError::FileAccessError(e.to_string()))?;
+        serde_yaml::to_writer(BufWriter::new(file), self).map_err(|e| ArchiveError::FormatError(e.to_string()))
+    }
+
+    /// The `session_info_update` last passed to `record`.
+    pub fn last_update(&self) -> Option<i32> {
+        self.last_update
+    }
+
+    /// The `session_num` last passed to `record`.
+    pub fn last_session_num(&self) -> Option<i32> {
+        self.last_session_num
+    }
+
+    /// Stores `yaml` unless it is the same string, in the same session, as
+    /// the newest entry. Returns whether it was stored.
+    pub fn record(&mut self, update: i32, session_num: Option<i32>, session_time: Option<f64>, yaml: &str) -> bool {
+        self.last_update = Some(update);
+        self.last_session_num = session_num;
+        if self.entries.last().is_some_and(|entry| entry.yaml == yaml && entry.session_num == session_num) {
+            return false;
+        }
+        self.entries.push(ArchivedSessionInfo { update, session_num, session_time, yaml: yaml.to_string() });
+        true
+    }
+
+    pub fn entries(&self) -> &[ArchivedSessionInfo] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The version that was current at `session_time` in session
+    /// `session_num`. `SessionTime` restarts with every session, so the
+    /// time alone does not pick a version.
+    pub fn at_session_time(&self, session_num: i32, session_time: f64) -> Option<&ArchivedSessionInfo> {
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| entry.session_num == Some(session_num))
+            .find(|entry| entry.session_time.is_some_and(|t| t <= session_time))
+    }
+}
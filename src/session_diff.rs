@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use crate::session::{Driver, SessionDocument, WeekendInfo};
+
+/// One thing that changed between two versions of the session info.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionInfoChange {
+    DriverJoined { car_idx: i64, user_name: Option<String> },
+    DriverLeft { car_idx: i64, user_name: Option<String> },
+    /// A different driver took over the car, e.g. a team driver swap.
+    DriverChanged { car_idx: i64, from: Option<String>, to: Option<String> },
+    /// `from` is `None` when the car was not classified before.
+    PositionChanged { session_num: i64, car_idx: i64, from: Option<i64>, to: i64 },
+    /// The car dropped out of the session's results, e.g. on a results reset.
+    PositionRemoved { session_num: i64, car_idx: i64, from: i64 },
+    /// `CurrentSessionNum` moved on, e.g. from qualifying to the race.
+    SessionChanged { from: Option<i64>, to: Option<i64> },
+    /// A `WeekendInfo` weather field, named by its YAML key.
+    WeatherChanged { field: &'static str, from: Option<String>, to: Option<String> },
+}
+
+impl fmt::Display for SessionInfoChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionInfoChange::DriverJoined { car_idx, user_name } => {
+                write!(f, "car {} joined: {}", car_idx, or_dash(user_name))
+            }
+            SessionInfoChange::DriverLeft { car_idx, user_name } => {
+                write!(f, "car {} left: {}", car_idx, or_dash(user_name))
+            }
+            SessionInfoChange::DriverChanged { car_idx, from, to } => {
+                write!(f, "car {} driver: {} -> {}", car_idx, or_dash(from), or_dash(to))
+            }
+            SessionInfoChange::PositionChanged { session_num, car_idx, from, to } => {
+                write!(f, "session {} car {}: P{} -> P{}", session_num, car_idx, or_dash(from), to)
+            }
+            SessionInfoChange::PositionRemoved { session_num, car_idx, from } => {
+                write!(f, "session {} car {}: P{} -> -", session_num, car_idx, from)
+            }
+            SessionInfoChange::SessionChanged { from, to } => write!(f, "session {} -> {}", or_dash(from), or_dash(to)),
+            SessionInfoChange::WeatherChanged { field, from, to } => {
+                write!(f, "{}: {} -> {}", field, or_dash(from), or_dash(to))
+            }
+        }
+    }
+}
+
+fn or_dash<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map_or_else(|| "-".to_string(), T::to_string)
+}
+
+/// The changes brought by one `session_info_update` bump.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfoEvent {
+    pub from_update: i32,
+    pub to_update: i32,
+    pub changes: Vec<SessionInfoChange>,
+}
+
+type WeatherField = fn(&WeekendInfo) -> &Option<String>;
+
+const WEATHER_FIELDS: &[(&str, WeatherField)] = &[
+    ("TrackWeatherType", |w| &w.track_weather_type),
+    ("TrackSkies", |w| &w.track_skies),
+    ("TrackSurfaceTemp", |w| &w.track_surface_temp),
+    ("TrackAirTemp", |w| &w.track_air_temp),
+    ("TrackAirPressure", |w| &w.track_air_pressure),
+    ("TrackWindVel", |w| &w.track_wind_vel),
+    ("TrackWindDir", |w| &w.track_wind_dir),
+    ("TrackRelativeHumidity", |w| &w.track_relative_humidity),
+    ("TrackFogLevel", |w| &w.track_fog_level),
+    ("TrackPrecipitation", |w| &w.track_precipitation),
+];
+
+/// Compares two versions of the session info: drivers, results positions,
+/// the current session and the weather.
+pub fn diff_session_info(old: &SessionDocument, new: &SessionDocument) -> Vec<SessionInfoChange> {
+    let mut changes = Vec::new();
+    diff_drivers(old, new, &mut changes);
+    diff_positions(old, new, &mut changes);
+
+    let current_session_num = |doc: &SessionDocument| doc.session_info.as_ref().and_then(|info| info.current_session_num);
+    let (from, to) = (current_session_num(old), current_session_num(new));
+    if from != to {
+        changes.push(SessionInfoChange::SessionChanged { from, to });
+    }
+
+    let default = WeekendInfo::default();
+    let old_weekend = old.weekend_info.as_ref().unwrap_or(&default);
+    let new_weekend = new.weekend_info.as_ref().unwrap_or(&default);
+    for (field, get) in WEATHER_FIELDS {
+        if get(old_weekend) != get(new_weekend) {
+            changes.push(SessionInfoChange::WeatherChanged {
+                field,
+                from: get(old_weekend).clone(),
+                to: get(new_weekend).clone(),
+            });
+        }
+    }
+    changes
+}
+
+fn drivers(doc: &SessionDocument) -> BTreeMap<i64, &Driver> {
+    let drivers = doc.driver_info.as_ref().and_then(|info| info.drivers.as_ref());
+    drivers.into_iter().flatten().filter_map(|driver| Some((driver.car_idx?, driver))).collect()
+}
+
+fn diff_drivers(old: &SessionDocument, new: &SessionDocument, changes: &mut Vec<SessionInfoChange>) {
+    let (old_drivers, new_drivers) = (drivers(old), drivers(new));
+    for (&car_idx, driver) in &old_drivers {
+        if !new_drivers.contains_key(&car_idx) {
+            changes.push(SessionInfoChange::DriverLeft { car_idx, user_name: driver.user_name.clone() });
+        }
+    }
+    for (&car_idx, driver) in &new_drivers {
+        match old_drivers.get(&car_idx) {
+            None => changes.push(SessionInfoChange::DriverJoined { car_idx, user_name: driver.user_name.clone() }),
+            Some(old_driver) if old_driver.user_name != driver.user_name => {
+                changes.push(SessionInfoChange::DriverChanged {
+                    car_idx,
+                    from: old_driver.user_name.clone(),
+                    to: driver.user_name.clone(),
+                });
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// Session number -> car index -> position.
+fn positions(doc: &SessionDocument) -> BTreeMap<i64, BTreeMap<i64, i64>> {
+    let sessions = doc.session_info.as_ref().and_then(|info| info.sessions.as_ref());
+    sessions
+        .into_iter()
+        .flatten()
+        .filter_map(|session| {
+            let results = session.results_positions.iter().flatten();
+            let positions = results.filter_map(|result| Some((result.car_idx?, result.position?))).collect();
+            Some((session.session_num?, positions))
+        })
+        .collect()
+}
+
+fn diff_positions(old: &SessionDocument, new: &SessionDocument, changes: &mut Vec<SessionInfoChange>) {
+    let (old_positions, new_positions) = (positions(old), positions(new));
+    for (&session_num, old_results) in &old_positions {
+        let new_results = new_positions.get(&session_num);
+        for (&car_idx, &from) in old_results {
+            if !new_results.is_some_and(|results| results.contains_key(&car_idx)) {
+                changes.push(SessionInfoChange::PositionRemoved { session_num, car_idx, from });
+            }
+        }
+    }
+    for (&session_num, new_results) in &new_positions {
+        let old_results = old_positions.get(&session_num);
+        for (&car_idx, &to) in new_results {
+            let from = old_results.and_then(|results| results.get(&car_idx)).copied();
+            if from != Some(to) {
+                changes.push(SessionInfoChange::PositionChanged { session_num, car_idx, from, to });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RACE: &str = "
+WeekendInfo:
+ TrackSkies: Partly Cloudy
+
+SessionInfo:
+ CurrentSessionNum: 1
+ Sessions:
+ - SessionNum: 1
+   ResultsPositions:
+   - Position: 1
+     CarIdx: 1
+   - Position: 2
+     CarIdx: 2
+
+DriverInfo:
+ Drivers:
+ - CarIdx: 0
+   UserName: Pace Car
+ - CarIdx: 1
+   UserName: Jane Doe
+ - CarIdx: 2
+   UserName: Max Mustermann
+";
+
+    fn document(yaml: &str) -> SessionDocument {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    fn diff(old: &str, new: &str) -> Vec<String> {
+        diff_session_info(&document(old), &document(new)).iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn reports_joins_positions_session_and_weather() {
+        let practice = "
+DriverInfo:
+ Drivers:
+ - CarIdx: 0
+   UserName: Pace Car
+ - CarIdx: 1
+   UserName: Jane Doe
+";
+        assert_eq!(
+            diff_session_info(&document(practice), &document(RACE)),
+            [
+                SessionInfoChange::DriverJoined { car_idx: 2, user_name: Some("Max Mustermann".to_string()) },
+                SessionInfoChange::PositionChanged { session_num: 1, car_idx: 1, from: None, to: 1 },
+                SessionInfoChange::PositionChanged { session_num: 1, car_idx: 2, from: None, to: 2 },
+                SessionInfoChange::SessionChanged { from: None, to: Some(1) },
+                SessionInfoChange::WeatherChanged { field: "TrackSkies", from: None, to: Some("Partly Cloudy".to_string()) },
+            ]
+        );
+        assert!(diff_session_info(&document(RACE), &document(RACE)).is_empty());
+    }
+
+    #[test]
+    fn reports_swaps_and_leaves() {
+        let swapped = RACE
+            .replace("Position: 1\n     CarIdx: 1", "Position: 1\n     CarIdx: 3")
+            .replace("Position: 2\n     CarIdx: 2", "Position: 2\n     CarIdx: 1")
+            .replace("Position: 1\n     CarIdx: 3", "Position: 1\n     CarIdx: 2")
+            .replace(" - CarIdx: 0\n   UserName: Pace Car\n", "")
+            .replace("UserName: Max Mustermann", "UserName: Erika Mustermann");
+        assert_eq!(
+            diff(RACE, &swapped),
+            [
+                "car 0 left: Pace Car",
+                "car 2 driver: Max Mustermann -> Erika Mustermann",
+                "session 1 car 1: P1 -> P2",
+                "session 1 car 2: P2 -> P1",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_cars_dropping_out_of_the_results() {
+        let reset = RACE.replace("   - Position: 2\n     CarIdx: 2\n", "");
+        assert_eq!(diff(RACE, &reset), ["session 1 car 2: P2 -> -"]);
+        let cleared = RACE.replace("   ResultsPositions:\n   - Position: 1\n     CarIdx: 1\n   - Position: 2\n     CarIdx: 2\n", "");
+        assert_eq!(
+            diff_session_info(&document(RACE), &document(&cleared)),
+            [
+                SessionInfoChange::PositionRemoved { session_num: 1, car_idx: 1, from: 1 },
+                SessionInfoChange::PositionRemoved { session_num: 1, car_idx: 2, from: 2 },
+            ]
+        );
+    }
+}
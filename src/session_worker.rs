@@ -0,0 +1,35 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use crate::session::ParsedSessionInfo;
+
+/// Parses session info strings on one background thread, so the caller's
+/// loop only copies the raw bytes. The thread exits when the worker is
+/// dropped.
+pub(crate) struct SessionWorker {
+    requests: Sender<(i32, Vec<u8>)>,
+    results: Receiver<(i32, Result<ParsedSessionInfo, serde_yaml::Error>)>,
+}
+
+impl SessionWorker {
+    pub(crate) fn spawn() -> Self {
+        let (requests, pending) = mpsc::channel::<(i32, Vec<u8>)>();
+        let (published, results) = mpsc::channel();
+        thread::spawn(move || {
+            while let Ok((update, binary_data)) = pending.recv() {
+                if published.send((update, ParsedSessionInfo::parse(update, &binary_data))).is_err() {
+                    break;
+                }
+            }
+        });
+        SessionWorker { requests, results }
+    }
+
+    pub(crate) fn parse(&self, update: i32, binary_data: Vec<u8>) {
+        let _ = self.requests.send((update, binary_data));
+    }
+
+    /// Results published since the last call, oldest first, without blocking.
+    pub(crate) fn results(&self) -> impl Iterator<Item = (i32, Result<ParsedSessionInfo, serde_yaml::Error>)> + '_ {
+        self.results.try_iter()
+    }
+}
@@ -0,0 +1,232 @@
+use serde_yaml::Value;
+use crate::query::scalar_to_string;
+use crate::units::{Quantity, Unit};
+
+/// One setup value, e.g. `StartingPressure: 24.5 psi`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SetupValue {
+    /// The text as the sim wrote it.
+    pub raw: String,
+    /// `None` for text such as `Soft` or lists such as `30C, 30C, 30C`.
+    pub quantity: Option<Quantity>,
+}
+
+impl SetupValue {
+    pub fn parse(raw: &str) -> Self {
+        SetupValue { raw: raw.to_string(), quantity: parse_quantity(raw) }
+    }
+
+    pub fn value(&self) -> Option<f64> {
+        self.quantity.as_ref().map(|quantity| quantity.value)
+    }
+
+    pub fn unit(&self) -> Option<&Unit> {
+        self.quantity.as_ref().map(|quantity| &quantity.unit)
+    }
+
+    /// The value converted to `unit`, if it has a compatible one.
+    pub fn to(&self, unit: Unit) -> Option<f64> {
+        self.quantity.as_ref()?.to(unit).map(|quantity| quantity.value)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetupNode {
+    Section(SetupSection),
+    Value(SetupValue),
+}
+
+impl SetupNode {
+    fn from_value(value: &Value) -> Self {
+        match value {
+            Value::Mapping(_) => SetupNode::Section(SetupSection::from_value(value)),
+            other => {
+                let raw = scalar_to_string(other)
+                    .unwrap_or_else(|| serde_yaml::to_string(other).unwrap_or_default().trim_end().to_string());
+                SetupNode::Value(SetupValue::parse(&raw))
+            }
+        }
+    }
+
+    pub fn as_section(&self) -> Option<&SetupSection> {
+        match self {
+            SetupNode::Section(section) => Some(section),
+            SetupNode::Value(_) => None,
+        }
+    }
+
+    pub fn as_value(&self) -> Option<&SetupValue> {
+        match self {
+            SetupNode::Value(value) => Some(value),
+            SetupNode::Section(_) => None,
+        }
+    }
+}
+
+/// A group of setup entries, e.g. `Tires` or `LeftFront`, in the order the
+/// sim wrote them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SetupSection {
+    entries: Vec<(String, SetupNode)>,
+}
+
+impl SetupSection {
+    pub fn from_value(value: &Value) -> Self {
+        let entries = match value {
+            Value::Mapping(mapping) => mapping
+                .iter()
+                .filter_map(|(key, value)| Some((scalar_to_string(key)?, SetupNode::from_value(value))))
+                .collect(),
+            _ => Vec::new(),
+        };
+        SetupSection { entries }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SetupNode> {
+        self.entries.iter().find(|(name, _)| name == key).map(|(_, node)| node)
+    }
+
+    pub fn section(&self, key: &str) -> Option<&SetupSection> {
+        self.get(key).and_then(SetupNode::as_section)
+    }
+
+    pub fn value(&self, key: &str) -> Option<&SetupValue> {
+        self.get(key).and_then(SetupNode::as_value)
+    }
+
+    /// Follows `path` through nested sections, e.g.
+    /// `["Tires", "LeftFront", "StartingPressure"]`.
+    pub fn find(&self, path: &[&str]) -> Option<&SetupNode> {
+        let (last, sections) = path.split_last()?;
+        let mut section = self;
+        for key in sections {
+            section = section.section(key)?;
+        }
+        section.get(last)
+    }
+
+    pub fn entries(&self) -> &[(String, SetupNode)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+// A number followed by at most one unit token, e.g. `-2.8 deg` or `1.850"`.
+// Only known units may follow the number directly, so gear ratios such as
+// `17:1` and ordinals such as `2nd` stay text.
+fn parse_quantity(raw: &str) -> Option<Quantity> {
+    let trimmed = raw.trim();
+    let end = trimmed.find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))).unwrap_or(trimmed.len());
+    let value = trimmed[..end].parse().ok()?;
+    let rest = &trimmed[end..];
+    let unit = rest.trim_start();
+    if unit.contains(|c: char| c.is_whitespace() || c == ',') {
+        return None;
+    }
+    let attached = unit.len() == rest.len() && !unit.is_empty();
+    match Unit::parse(unit) {
+        Unit::Other(_) if attached => None,
+        unit => Some(Quantity::new(value, unit)),
+    }
+}
+
+/// The `CarSetup` section with every value split into a number and unit.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CarSetupTree {
+    pub update_count: Option<i64>,
+    /// Top-level sections such as `Tires` and `Chassis`.
+    pub root: SetupSection,
+}
+
+impl CarSetupTree {
+    /// Builds the tree from a `CarSetup` mapping.
+    pub fn from_value(car_setup: &Value) -> Self {
+        let mut root = SetupSection::from_value(car_setup);
+        let update_count = car_setup.get("UpdateCount").and_then(Value::as_i64);
+        root.entries.retain(|(key, _)| key != "UpdateCount");
+        CarSetupTree { update_count, root }
+    }
+
+    /// Builds the tree from a whole session info document, if it has a
+    /// `CarSetup` section.
+    pub fn from_session_value(session_info: &Value) -> Option<Self> {
+        session_info.get("CarSetup").map(CarSetupTree::from_value)
+    }
+
+    pub fn section(&self, key: &str) -> Option<&SetupSection> {
+        self.root.section(key)
+    }
+
+    pub fn find(&self, path: &[&str]) -> Option<&SetupNode> {
+        self.root.find(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAR_SETUP: &str = "
+UpdateCount: 2
+Tires:
+ LeftFront:
+  StartingPressure: 24.5 psi
+  LastTempsOMI: 30C, 30C, 30C
+Chassis:
+ Front:
+  Camber: -2.8 deg
+  SpringRate: 105 N/mm
+  BrakePads: Medium friction
+  BrakeBias: 54.2%
+ Drivetrain:
+  GearRatio: 17:1
+  FirstGear: 2nd
+  DiffClutches: 6 plates
+";
+
+    fn car_setup() -> CarSetupTree {
+        CarSetupTree::from_value(&serde_yaml::from_str(CAR_SETUP).unwrap())
+    }
+
+    #[test]
+    fn keeps_sections_in_order() {
+        let setup = car_setup();
+        assert_eq!(setup.update_count, Some(2));
+        let sections: Vec<&str> = setup.root.entries().iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(sections, ["Tires", "Chassis"]);
+        assert!(setup.find(&["Tires", "LeftFront"]).and_then(SetupNode::as_section).is_some());
+        assert!(setup.find(&["Tires", "RightFront", "StartingPressure"]).is_none());
+        assert!(setup.find(&[]).is_none());
+    }
+
+    #[test]
+    fn splits_values_into_quantities() {
+        let setup = car_setup();
+        let pressure = setup.find(&["Tires", "LeftFront", "StartingPressure"]).and_then(SetupNode::as_value).unwrap();
+        assert_eq!(pressure.raw, "24.5 psi");
+        assert_eq!(pressure.quantity, Some(Quantity::new(24.5, Unit::Psi)));
+        let temps = setup.section("Tires").and_then(|tires| tires.section("LeftFront")).unwrap().value("LastTempsOMI");
+        assert_eq!(temps.map(|v| (v.raw.as_str(), v.quantity.is_none())), Some(("30C, 30C, 30C", true)));
+
+        let front = setup.section("Chassis").and_then(|chassis| chassis.section("Front")).unwrap();
+        assert_eq!(front.value("Camber").and_then(SetupValue::value), Some(-2.8));
+        assert_eq!(front.value("Camber").and_then(SetupValue::unit), Some(&Unit::Degrees));
+        let spring_rate = front.value("SpringRate").unwrap();
+        assert!((spring_rate.to(Unit::PoundsPerInch).unwrap() - 599.567).abs() < 0.01);
+        assert_eq!(front.value("BrakePads").map(|v| v.quantity.is_none()), Some(true));
+        assert_eq!(front.value("BrakeBias").and_then(|v| v.quantity.clone()), Some(Quantity::new(54.2, Unit::Percent)));
+
+        let drivetrain = setup.section("Chassis").and_then(|chassis| chassis.section("Drivetrain")).unwrap();
+        assert_eq!(drivetrain.value("GearRatio").map(|v| (v.raw.as_str(), v.quantity.is_none())), Some(("17:1", true)));
+        assert_eq!(drivetrain.value("FirstGear").map(|v| (v.raw.as_str(), v.quantity.is_none())), Some(("2nd", true)));
+        let plates = Quantity::new(6.0, Unit::Other("plates".to_string()));
+        assert_eq!(drivetrain.value("DiffClutches").and_then(|v| v.quantity.clone()), Some(plates));
+    }
+}
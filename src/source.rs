@@ -0,0 +1,161 @@
+use std::fs::File;
+use memmap2::{Mmap, MmapOptions};
+use crate::irsdk::IRSDKError;
+
+#[cfg(windows)]
+use std::slice;
+#[cfg(windows)]
+use crate::constants::*;
+#[cfg(windows)]
+use reqwest::blocking::Client;
+#[cfg(windows)]
+use windows::core::PCWSTR;
+#[cfg(windows)]
+use windows::Win32::Foundation::{CloseHandle, HANDLE, WAIT_OBJECT_0};
+#[cfg(windows)]
+use windows::Win32::System::Memory::{MapViewOfFile, OpenFileMappingW, UnmapViewOfFile, FILE_MAP_READ, MEMORYMAPPEDVIEW_HANDLE};
+#[cfg(windows)]
+use windows::Win32::System::Threading::{OpenEventW, WaitForSingleObject, SYNCHRONIZATION_SYNCHRONIZE};
+
+#[cfg(windows)]
+pub type DefaultSource = WindowsSource;
+#[cfg(not(windows))]
+pub type DefaultSource = FileSource;
+
+/// Backing storage for the iRacing telemetry layout (header, var headers,
+/// session info and rotating var buffers).
+pub trait TelemetrySource: Sized {
+    /// Opens the source. `test_file` is the path of a memory dump, if any.
+    fn open(test_file: Option<&str>) -> Result<Self, IRSDKError>;
+
+    /// The whole mapped region, starting at the telemetry header.
+    fn memory(&self) -> &[u8];
+
+    /// Blocks for up to `timeout_ms` until the producer signals a new tick.
+    fn wait_for_data(&self, timeout_ms: u32) -> bool;
+
+    /// Whether the data is produced by a running sim rather than a dump.
+    fn is_live(&self) -> bool;
+}
+
+/// A memory dump (see `IRSDK::startup`'s `dump_to`) mapped read-only from disk.
+pub struct FileSource {
+    mem: Mmap,
+}
+
+impl FileSource {
+    pub fn from_path(path: &str) -> Result<Self, IRSDKError> {
+        let file = File::open(path).map_err(|e| IRSDKError::ConnectionFailed(e.to_string()))?;
+        let mem = unsafe {
+            MmapOptions::new()
+                .map(&file)
+                .map_err(|_| IRSDKError::MemoryAccessError)?
+        };
+        Ok(FileSource { mem })
+    }
+}
+
+impl TelemetrySource for FileSource {
+    fn open(test_file: Option<&str>) -> Result<Self, IRSDKError> {
+        match test_file {
+            Some(path) => FileSource::from_path(path),
+            None => Err(IRSDKError::ConnectionFailed("FileSource requires a dump file path".to_string())),
+        }
+    }
+
+    fn memory(&self) -> &[u8] {
+        &self.mem[..]
+    }
+
+    fn wait_for_data(&self, _timeout_ms: u32) -> bool {
+        true
+    }
+
+    fn is_live(&self) -> bool {
+        false
+    }
+}
+
+/// The live shared memory mapping published by the sim.
+#[cfg(windows)]
+pub struct WindowsSource {
+    data_valid_event: HANDLE,
+    mapping: HANDLE,
+    view: MEMORYMAPPEDVIEW_HANDLE,
+}
+
+#[cfg(windows)]
+impl WindowsSource {
+    fn check_sim_status() -> Result<(), IRSDKError> {
+        let client = Client::new();
+        let response = client
+            .get(SIM_STATUS_URL)
+            .send()
+            .map_err(|e| IRSDKError::ConnectionFailed(format!("Failed connect to IRacing: {}", e)))?;
+        match response.text() {
+            Ok(text) if text.contains("running:1") => Ok(()),
+            _ => Err(IRSDKError::ConnectionFailed("IRacing is not running".to_string())),
+        }
+    }
+}
+
+#[cfg(windows)]
+impl TelemetrySource for WindowsSource {
+    fn open(test_file: Option<&str>) -> Result<Self, IRSDKError> {
+        if test_file.is_some() {
+            return Err(IRSDKError::ConnectionFailed("WindowsSource only maps live data, use FileSource for dumps".to_string()));
+        }
+        Self::check_sim_status()?;
+
+        let event_name_w: Vec<u16> = DATA_VALID_EVENT_NAME.encode_utf16().chain(std::iter::once(0)).collect();
+        let data_valid_event = unsafe {
+            OpenEventW(SYNCHRONIZATION_SYNCHRONIZE, false, PCWSTR(event_name_w.as_ptr()))
+                .map_err(|e| IRSDKError::WindowsAPIError(format!("Failed to open event: {}", e)))?
+        };
+
+        let map_name_w: Vec<u16> = MEM_MAP_FILE.encode_utf16().chain(std::iter::once(0)).collect();
+        let mapping = match unsafe { OpenFileMappingW(FILE_MAP_READ.0, false, PCWSTR(map_name_w.as_ptr())) } {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                unsafe { CloseHandle(data_valid_event) };
+                return Err(IRSDKError::WindowsAPIError(format!("Failed to open file mapping: {}", e)));
+            }
+        };
+
+        let view = match unsafe { MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, MEM_MAP_FILE_SIZE) } {
+            Ok(view) => view,
+            Err(e) => {
+                unsafe {
+                    CloseHandle(mapping);
+                    CloseHandle(data_valid_event);
+                }
+                return Err(IRSDKError::WindowsAPIError(format!("Failed to map view of file: {}", e)));
+            }
+        };
+
+        Ok(WindowsSource { data_valid_event, mapping, view })
+    }
+
+    fn memory(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.view.0 as *const u8, MEM_MAP_FILE_SIZE) }
+    }
+
+    fn wait_for_data(&self, timeout_ms: u32) -> bool {
+        unsafe { WaitForSingleObject(self.data_valid_event, timeout_ms) == WAIT_OBJECT_0 }
+    }
+
+    fn is_live(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(windows)]
+impl Drop for WindowsSource {
+    fn drop(&mut self) {
+        unsafe {
+            UnmapViewOfFile(self.view);
+            CloseHandle(self.mapping);
+            CloseHandle(self.data_valid_event);
+        }
+    }
+}
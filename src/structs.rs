@@ -1,52 +1,103 @@
-use std::slice;
-use memmap2::Mmap;
-use byteorder::{LittleEndian, ReadBytesExt};
+use std::borrow::Cow;
+use std::fmt;
+use std::ptr;
+use std::sync::atomic::{fence, Ordering};
+use encoding_rs::WINDOWS_1252;
+use crate::constants::*;
+use crate::units::Unit;
 
-pub struct IRSDKStruct {
-    shared_mem: Mmap,
+#[derive(Debug)]
+pub enum ParseError {
+    /// `what` spans `offset..offset + len`, past the end of a `size` byte buffer.
+    OutOfBounds { what: &'static str, offset: i64, len: i64, size: usize },
+    /// A count read from the header is negative or larger than the format allows.
+    InvalidCount { what: &'static str, value: i32 },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::OutOfBounds { what, offset, len, size } => {
+                write!(f, "{} at {}..{} is past the end of a {} byte buffer", what, offset, offset + len, size)
+            }
+            ParseError::InvalidCount { what, value } => write!(f, "invalid {}: {}", what, value),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Decodes text the sim wrote in its code page (`YAML_CODE_PAGE`,
+/// Windows-1252). Borrows when the bytes are plain ASCII.
+pub fn decode_cp1252(bytes: &[u8]) -> Cow<'_, str> {
+    WINDOWS_1252.decode_without_bom_handling(bytes).0
+}
+
+pub(crate) fn check_region(mem: &[u8], what: &'static str, offset: i64, len: i64) -> Result<(), ParseError> {
+    if offset < 0 || len < 0 || offset + len > mem.len() as i64 {
+        return Err(ParseError::OutOfBounds { what, offset, len, size: mem.len() });
+    }
+    Ok(())
+}
+
+#[derive(Clone, Copy)]
+pub struct IRSDKStruct<'a> {
+    shared_mem: &'a [u8],
     offset: usize,
 }
 
-impl IRSDKStruct {
-    pub fn new(shared_mem: Mmap, offset: usize) -> Self {
+impl<'a> IRSDKStruct<'a> {
+    pub fn new(shared_mem: &'a [u8], offset: usize) -> Self {
         IRSDKStruct { shared_mem, offset }
     }
 
-    pub fn get_i8(&self, offset: usize) -> i8 {
-        self.shared_mem[self.offset + offset] as i8
+    fn bytes(&self, offset: usize, len: usize) -> Result<&'a [u8], ParseError> {
+        let start = self.offset as i64 + offset as i64;
+        check_region(self.shared_mem, "field", start, len as i64)?;
+        Ok(&self.shared_mem[start as usize..start as usize + len])
+    }
+
+    pub fn get_i8(&self, offset: usize) -> Result<i8, ParseError> {
+        Ok(self.bytes(offset, 1)?[0] as i8)
+    }
+
+    pub fn get_bool(&self, offset: usize) -> Result<bool, ParseError> {
+        Ok(self.bytes(offset, 1)?[0] != 0)
     }
 
-    pub fn get_bool(&self, offset: usize) -> bool {
-        self.shared_mem[self.offset + offset] != 0
+    pub fn get_i32(&self, offset: usize) -> Result<i32, ParseError> {
+        Ok(i32::from_le_bytes(self.bytes(offset, 4)?.try_into().unwrap()))
     }
 
-    pub fn get_i32(&self, offset: usize) -> i32 {
-        let slice = &self.shared_mem[self.offset + offset..self.offset + offset + 4];
-        i32::from_le_bytes(slice.try_into().unwrap())
+    pub fn get_u32(&self, offset: usize) -> Result<u32, ParseError> {
+        Ok(u32::from_le_bytes(self.bytes(offset, 4)?.try_into().unwrap()))
     }
 
-    pub fn get_u32(&self, offset: usize) -> u32 {
-        let slice = &self.shared_mem[self.offset + offset..self.offset + offset + 4];
-        u32::from_le_bytes(slice.try_into().unwrap())
+    pub fn get_u64(&self, offset: usize) -> Result<u64, ParseError> {
+        Ok(u64::from_le_bytes(self.bytes(offset, 8)?.try_into().unwrap()))
     }
 
-    pub fn get_f32(&self, offset: usize) -> f32 {
-        let slice = &self.shared_mem[self.offset + offset..self.offset + offset + 4];
-        f32::from_le_bytes(slice.try_into().unwrap())
+    pub fn get_f32(&self, offset: usize) -> Result<f32, ParseError> {
+        Ok(f32::from_le_bytes(self.bytes(offset, 4)?.try_into().unwrap()))
     }
 
-    pub fn get_f64(&self, offset: usize) -> f64 {
-        let slice = &self.shared_mem[self.offset + offset..self.offset + offset + 8];
-        f64::from_le_bytes(slice.try_into().unwrap())
+    pub fn get_f64(&self, offset: usize) -> Result<f64, ParseError> {
+        Ok(f64::from_le_bytes(self.bytes(offset, 8)?.try_into().unwrap()))
     }
 
-    pub fn get_str(&self, offset: usize, len: usize) -> String {
-        let slice = &self.shared_mem[self.offset + offset..self.offset + offset + len];
-        String::from_utf8_lossy(&slice[..slice.iter().position(|&x| x == 0).unwrap_or(len)]).to_string()
+    /// Decodes the NUL-terminated Windows-1252 string, borrowing it when it
+    /// is plain ASCII.
+    pub fn get_str(&self, offset: usize, len: usize) -> Result<Cow<'a, str>, ParseError> {
+        let slice = self.bytes(offset, len)?;
+        Ok(decode_cp1252(&slice[..slice.iter().position(|&x| x == 0).unwrap_or(len)]))
     }
 }
 
-pub struct Header {
+/// The telemetry header at the start of the shared memory or of an `.ibt`
+/// file. Borrows the whole region so var buffers, var headers and session
+/// info can be reached without copying.
+#[derive(Clone, Copy)]
+pub struct Header<'a> {
     pub version: i32,
     pub status: i32,
     pub tick_rate: i32,
@@ -57,119 +108,236 @@ pub struct Header {
     pub var_header_offset: i32,
     pub num_buf: i32,
     pub buf_len: i32,
-    pub var_buf: Vec<VarBuffer>,
-}
-
-impl Header {
-    pub fn from_struct(irsdk_struct: &IRSDKStruct) -> Self {
-        let version = irsdk_struct.get_i32(0);
-        let status = irsdk_struct.get_i32(4);
-        let tick_rate = irsdk_struct.get_i32(8);
-        let session_info_update = irsdk_struct.get_i32(12);
-        let session_info_len = irsdk_struct.get_i32(16);
-        let session_info_offset = irsdk_struct.get_i32(20);
-        let num_vars = irsdk_struct.get_i32(24);
-        let var_header_offset = irsdk_struct.get_i32(28);
-        let num_buf = irsdk_struct.get_i32(32);
-        let buf_len = irsdk_struct.get_i32(36);
-
-        let mut var_buf = Vec::new();
-        for i in 0..num_buf {
-            let buf_offset = 48 + (i as usize) * 16;
-            let var_buffer = VarBuffer::from_struct(irsdk_struct, buf_offset, buf_len);
-            var_buf.push(var_buffer);
-        }
+    mem: &'a [u8],
+}
 
-        Header {
-            version,
-            status,
-            tick_rate,
-            session_info_update,
-            session_info_len,
-            session_info_offset,
-            num_vars,
-            var_header_offset,
-            num_buf,
-            buf_len,
-            var_buf,
+impl<'a> Header<'a> {
+    /// Parses the header and checks that the var header table, session info
+    /// and every var buffer it points at lie inside `mem`.
+    pub fn parse(mem: &'a [u8]) -> Result<Self, ParseError> {
+        check_region(mem, "header", 0, HEADER_SIZE as i64)?;
+        let irsdk_struct = IRSDKStruct::new(mem, 0);
+        let header = Header {
+            version: irsdk_struct.get_i32(0)?,
+            status: irsdk_struct.get_i32(4)?,
+            tick_rate: irsdk_struct.get_i32(8)?,
+            session_info_update: irsdk_struct.get_i32(12)?,
+            session_info_len: irsdk_struct.get_i32(16)?,
+            session_info_offset: irsdk_struct.get_i32(20)?,
+            num_vars: irsdk_struct.get_i32(24)?,
+            var_header_offset: irsdk_struct.get_i32(28)?,
+            num_buf: irsdk_struct.get_i32(32)?,
+            buf_len: irsdk_struct.get_i32(36)?,
+            mem,
+        };
+
+        if header.num_vars < 0 {
+            return Err(ParseError::InvalidCount { what: "num_vars", value: header.num_vars });
         }
+        if !(0..=MAX_BUFS as i32).contains(&header.num_buf) {
+            return Err(ParseError::InvalidCount { what: "num_buf", value: header.num_buf });
+        }
+        if header.buf_len < 0 {
+            return Err(ParseError::InvalidCount { what: "buf_len", value: header.buf_len });
+        }
+        check_region(
+            mem,
+            "var headers",
+            header.var_header_offset as i64,
+            header.num_vars as i64 * VAR_HEADER_SIZE as i64,
+        )?;
+        check_region(mem, "session info", header.session_info_offset as i64, header.session_info_len as i64)?;
+        for i in 0..header.num_buf as usize {
+            let var_buf = VarBuffer::parse(mem, 48 + i * 16, header.buf_len)?;
+            check_region(mem, "var buffer", var_buf.buf_offset as i64, header.buf_len as i64)?;
+        }
+        Ok(header)
     }
-}
 
-pub struct VarBuffer {
-    pub tick_count: i32,
-    pub buf_offset: i32,
-    pub is_memory_frozen: bool,
-    pub frozen_memory: Option<Vec<u8>>,
-    pub buf_len: i32,
-}
+    pub fn memory(&self) -> &'a [u8] {
+        self.mem
+    }
 
-impl VarBuffer {
-    pub fn from_struct(irsdk_struct: &IRSDKStruct, offset: usize, buf_len: i32) -> Self {
-        let tick_count = irsdk_struct.get_i32(offset);
-        let buf_offset = irsdk_struct.get_i32(offset + 4);
-        VarBuffer {
-            tick_count,
-            buf_offset,
-            is_memory_frozen: false,
-            frozen_memory: None,
-            buf_len,
+    pub fn var_buf(&self, index: usize) -> Option<VarBuffer<'a>> {
+        if index >= self.num_buf.max(0) as usize {
+            return None;
         }
+        VarBuffer::parse(self.mem, 48 + index * 16, self.buf_len).ok()
     }
 
-    pub fn freeze(&mut self, shared_mem: &Mmap) {
-        if self.buf_offset >= 0 && (self.buf_offset as usize + self.buf_len as usize) <= shared_mem.len() {
-            self.frozen_memory = Some(shared_mem[self.buf_offset as usize..self.buf_offset as usize + self.buf_len as usize].to_vec());
-            self.is_memory_frozen = true;
+    pub fn var_bufs(&self) -> impl Iterator<Item = VarBuffer<'a>> + '_ {
+        (0..self.num_buf.max(0) as usize).filter_map(|i| self.var_buf(i))
+    }
+
+    /// Re-reads the tick count of buffer `index` from memory. The sim writes
+    /// it from another process, so the read is volatile: it is never cached
+    /// or merged with an earlier read of the same field.
+    pub fn read_tick_count(&self, index: usize) -> Option<i32> {
+        if index >= self.num_buf.max(0) as usize {
+            return None;
         }
+        read_i32_volatile(self.mem, 48 + index * 16)
     }
 
-    pub fn unfreeze(&mut self) {
-        self.frozen_memory = None;
-        self.is_memory_frozen = false;
+    /// Copies the newest var buffer with `copy` and returns it with its tick
+    /// count, or `None` if the sim rotated into that buffer during the copy.
+    pub(crate) fn copy_latest_var_buf(&self, copy: impl FnOnce(&'a [u8]) -> Vec<u8>) -> Option<(i32, Vec<u8>)> {
+        let (index, var_buf) = self.var_bufs().enumerate().max_by_key(|(_, v)| v.tick_count)?;
+        let tick_count = self.read_tick_count(index)?;
+        fence(Ordering::Acquire);
+        let data = copy(var_buf.data()?);
+        fence(Ordering::Acquire);
+        (self.read_tick_count(index)? == tick_count).then_some((tick_count, data))
     }
 
-    pub fn get_memory<'a>(&self, shared_mem: &'a Mmap) -> &'a [u8] {
-        if self.is_memory_frozen {
-            self.frozen_memory.as_ref().unwrap()
-        } else {
-            &shared_mem[self.buf_offset as usize..self.buf_offset as usize + self.buf_len as usize]
+    /// The buffer with the highest tick count.
+    pub fn latest_var_buf(&self) -> Option<VarBuffer<'a>> {
+        self.var_bufs().max_by_key(|v| v.tick_count)
+    }
+
+    pub fn var_header(&self, index: usize) -> Option<VarHeader<'a>> {
+        if index >= self.num_vars.max(0) as usize {
+            return None;
         }
+        let offset = self.var_header_offset as usize + index * VAR_HEADER_SIZE;
+        VarHeader::parse(self.mem, offset).ok()
     }
 
-    pub fn get_buf_offset(&self) -> i32 {
-        if self.is_memory_frozen {
-            0
-        } else {
-            self.buf_offset
+    pub fn var_headers(&self) -> impl Iterator<Item = VarHeader<'a>> + '_ {
+        (0..self.num_vars.max(0) as usize).filter_map(|i| self.var_header(i))
+    }
+
+    pub fn session_info(&self) -> Option<&'a [u8]> {
+        let start = self.session_info_offset as usize;
+        self.mem.get(start..start + self.session_info_len as usize)
+    }
+}
+
+/// The var buffer geometry of a header that passed `Header::parse`, so reads
+/// can skip validating the whole header again. It goes stale when the mapping
+/// changes size or the sim bumps `session_info_update`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct BufferLayout {
+    num_buf: usize,
+    buf_len: usize,
+    mem_len: usize,
+    session_info_update: i32,
+}
+
+impl BufferLayout {
+    pub fn new(header: &Header<'_>) -> Self {
+        BufferLayout {
+            num_buf: header.num_buf.max(0) as usize,
+            buf_len: header.buf_len.max(0) as usize,
+            mem_len: header.mem.len(),
+            session_info_update: header.session_info_update,
         }
     }
+
+    pub fn matches(&self, mem: &[u8]) -> bool {
+        mem.len() == self.mem_len && read_i32_volatile(mem, 12) == Some(self.session_info_update)
+    }
+
+    /// Reads only the `tick_count`/`buf_offset` pair of buffer `index`.
+    fn var_buf<'a>(&self, mem: &'a [u8], index: usize) -> Option<(i32, &'a [u8])> {
+        let tick_count = read_i32_volatile(mem, 48 + index * 16)?;
+        let start = usize::try_from(read_i32_volatile(mem, 52 + index * 16)?).ok()?;
+        Some((tick_count, mem.get(start..start.checked_add(self.buf_len)?)?))
+    }
+
+    pub fn latest_tick_count(&self, mem: &[u8]) -> Option<i32> {
+        (0..self.num_buf).filter_map(|i| self.var_buf(mem, i)).map(|(tick_count, _)| tick_count).max()
+    }
+
+    /// The second newest buffer; the newest one may still be being written.
+    pub fn second_newest<'a>(&self, mem: &'a [u8]) -> Option<&'a [u8]> {
+        let newest = self.latest_tick_count(mem)?;
+        (0..self.num_buf)
+            .filter_map(|i| self.var_buf(mem, i))
+            .filter(|(tick_count, _)| *tick_count < newest)
+            .max_by_key(|(tick_count, _)| *tick_count)
+            .map(|(_, data)| data)
+    }
 }
 
-pub struct VarHeader {
+fn read_i32_volatile(mem: &[u8], offset: usize) -> Option<i32> {
+    let field = mem.get(offset..offset + 4)?;
+    // [u8; 4] has no alignment requirement, and `field` is 4 bytes long.
+    let bytes = unsafe { ptr::read_volatile(field.as_ptr() as *const [u8; 4]) };
+    Some(i32::from_le_bytes(bytes))
+}
+
+#[derive(Clone, Copy)]
+pub struct VarBuffer<'a> {
+    pub tick_count: i32,
+    pub buf_offset: i32,
+    pub buf_len: i32,
+    mem: &'a [u8],
+}
+
+impl<'a> VarBuffer<'a> {
+    pub fn parse(mem: &'a [u8], offset: usize, buf_len: i32) -> Result<Self, ParseError> {
+        let irsdk_struct = IRSDKStruct::new(mem, offset);
+        Ok(VarBuffer {
+            tick_count: irsdk_struct.get_i32(0)?,
+            buf_offset: irsdk_struct.get_i32(4)?,
+            buf_len,
+            mem,
+        })
+    }
+
+    pub fn data(&self) -> Option<&'a [u8]> {
+        let start = self.buf_offset as usize;
+        self.mem.get(start..start + self.buf_len as usize)
+    }
+}
+
+/// One entry of the var header table. Strings borrow from the backing memory;
+/// use `into_owned` to keep a header past the lifetime of the mapping.
+#[derive(Debug, Clone)]
+pub struct VarHeader<'a> {
     pub var_type: i32,
     pub offset: i32,
     pub count: i32,
     pub count_as_time: bool,
-    pub name: String,
-    pub desc: String,
-    pub unit: String,
+    pub name: Cow<'a, str>,
+    pub desc: Cow<'a, str>,
+    pub unit: Cow<'a, str>,
 }
 
-impl VarHeader {
-    pub fn from_struct(irsdk_struct: &IRSDKStruct, offset: usize) -> Self {
+impl<'a> VarHeader<'a> {
+    pub fn parse(mem: &'a [u8], offset: usize) -> Result<Self, ParseError> {
+        let irsdk_struct = IRSDKStruct::new(mem, offset);
+        Ok(VarHeader {
+            var_type: irsdk_struct.get_i32(0)?,
+            offset: irsdk_struct.get_i32(4)?,
+            count: irsdk_struct.get_i32(8)?,
+            count_as_time: irsdk_struct.get_bool(12)?,
+            name: irsdk_struct.get_str(16, 32)?,
+            desc: irsdk_struct.get_str(48, 64)?,
+            unit: irsdk_struct.get_str(112, 32)?,
+        })
+    }
+
+    /// Flagged `count_as_time`, or measured in seconds like `SessionTime`.
+    pub fn is_time(&self) -> bool {
+        self.count_as_time || Unit::parse(&self.unit) == Unit::Seconds
+    }
+
+    pub fn into_owned(self) -> VarHeader<'static> {
         VarHeader {
-            var_type: irsdk_struct.get_i32(offset),
-            offset: irsdk_struct.get_i32(offset + 4),
-            count: irsdk_struct.get_i32(offset + 8),
-            count_as_time: irsdk_struct.get_bool(offset + 12),
-            name: irsdk_struct.get_str(offset + 16, 32),
-            desc: irsdk_struct.get_str(offset + 48, 64),
-            unit: irsdk_struct.get_str(offset + 112, 32),
+            var_type: self.var_type,
+            offset: self.offset,
+            count: self.count,
+            count_as_time: self.count_as_time,
+            name: Cow::Owned(self.name.into_owned()),
+            desc: Cow::Owned(self.desc.into_owned()),
+            unit: Cow::Owned(self.unit.into_owned()),
         }
     }
 }
 
+#[derive(Clone, Copy)]
 pub struct DiskSubHeader {
     pub session_start_date: u64,
     pub session_start_time: f64,
@@ -179,21 +347,76 @@ pub struct DiskSubHeader {
 }
 
 impl DiskSubHeader {
-    pub fn from_struct(irsdk_struct: &IRSDKStruct, offset: usize) -> Self {
-        let slice = &irsdk_struct.shared_mem[offset..offset + 8];
-        let session_start_date = u64::from_le_bytes(slice.try_into().unwrap());
-        let session_start_time = irsdk_struct.get_f64(offset + 8);
-        let session_end_time = irsdk_struct.get_f64(offset + 16);
-        let session_lap_count = irsdk_struct.get_i32(offset + 24);
-        let session_record_count = irsdk_struct.get_i32(offset + 28);
-        DiskSubHeader {
-            session_start_date,
-            session_start_time,
-            session_end_time,
-            session_lap_count,
-            session_record_count,
-        }
+    pub fn parse(mem: &[u8], offset: usize) -> Result<Self, ParseError> {
+        let irsdk_struct = IRSDKStruct::new(mem, offset);
+        Ok(DiskSubHeader {
+            session_start_date: irsdk_struct.get_u64(0)?,
+            session_start_time: irsdk_struct.get_f64(8)?,
+            session_end_time: irsdk_struct.get_f64(16)?,
+            session_lap_count: irsdk_struct.get_i32(24)?,
+            session_record_count: irsdk_struct.get_i32(28)?,
+        })
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use std::slice;
+    use super::*;
+    use crate::fake_sim::FakeSim;
+    use crate::value::{VarType, VarValue};
 
+    // Three ticks into a fake sim: tick 3, the newest, sits in buffer 0.
+    fn telemetry() -> Vec<u8> {
+        let mut sim = FakeSim::new();
+        sim.add_var("Gear", VarType::Int, 1, "", "Current gear");
+        for gear in 1..=3 {
+            sim.set("Gear", VarValue::Int(gear)).unwrap();
+            sim.tick();
+        }
+        sim.bytes().to_vec()
+    }
+
+    // Plays the sim writing `tick` into buffer `index` halfway through the
+    // reader's copy.
+    fn copy_with_rotation(index: usize, tick: i32) -> Option<(i32, Vec<u8>)> {
+        let mut mem = telemetry();
+        let (ptr, len) = (mem.as_mut_ptr(), mem.len());
+        let header = Header::parse(unsafe { slice::from_raw_parts(ptr, len) }).unwrap();
+        let buf_offset = header.var_buf(index).unwrap().buf_offset as usize;
+        header.copy_latest_var_buf(|data| {
+            let mut copy = data[..2].to_vec();
+            unsafe {
+                ptr::write_volatile(ptr.add(buf_offset) as *mut [u8; 4], tick.to_le_bytes());
+                ptr::write_volatile(ptr.add(48 + index * 16) as *mut [u8; 4], tick.to_le_bytes());
+            }
+            copy.extend_from_slice(&data[2..]);
+            copy
+        })
+    }
+
+    #[test]
+    fn copies_the_newest_buffer() {
+        let mem = telemetry();
+        let (tick_count, data) = Header::parse(&mem).unwrap().copy_latest_var_buf(<[u8]>::to_vec).unwrap();
+        assert_eq!((tick_count, &data[..4]), (3, &3i32.to_le_bytes()[..]));
+    }
+
+    #[test]
+    fn rejects_a_copy_the_sim_rotated_into() {
+        assert_eq!(copy_with_rotation(0, 6), None);
+        let (tick_count, data) = copy_with_rotation(1, 4).unwrap();
+        assert_eq!((tick_count, &data[..4]), (3, &3i32.to_le_bytes()[..]));
+    }
+
+    #[test]
+    fn layout_reads_the_second_newest_buffer() {
+        let mut mem = telemetry();
+        let layout = BufferLayout::new(&Header::parse(&mem).unwrap());
+        assert_eq!(layout.latest_tick_count(&mem), Some(3));
+        assert_eq!(&layout.second_newest(&mem).unwrap()[..4], &2i32.to_le_bytes()[..]);
+        assert!(layout.matches(&mem));
+        mem[12] += 1;
+        assert!(!layout.matches(&mem));
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::handle::{VarHandle, VarScalar};
+use crate::structs::VarHeader;
+use crate::time::SessionTime;
+use crate::value::{VarError, VarValue};
+
+/// A struct filled from one var buffer, usually via `#[derive(Telemetry)]`.
+///
+/// Each field maps to the var named after it in CamelCase (`lap_dist_pct`
+/// reads `LapDistPct`), or to `#[irsdk(name = "...")]`. `bind` resolves every
+/// var once; `read` then fills the struct from a frame or IBT record.
+pub trait Telemetry: Sized {
+    type Handles: Clone + Send + Sync + 'static;
+
+    /// Fails with `UnknownVar` or `TypeMismatch` on the first field that does
+    /// not match the var layout.
+    fn bind(var_headers: &HashMap<String, VarHeader<'static>>) -> Result<Self::Handles, VarError>;
+
+    fn read(handles: &Self::Handles, data: &[u8]) -> Result<Self, VarError>;
+}
+
+/// A field type of a `Telemetry` struct: any `VarScalar`, a `Vec` of one for
+/// array vars, or a time.
+pub trait TelemetryField: Sized {
+    type Handle: Clone + Send + Sync + 'static;
+
+    fn bind(var_header: &VarHeader<'static>) -> Result<Self::Handle, VarError>;
+
+    fn read(handle: &Self::Handle, data: &[u8]) -> Option<Self>;
+}
+
+impl<T: VarScalar> TelemetryField for T {
+    type Handle = VarHandle<T>;
+
+    fn bind(var_header: &VarHeader<'static>) -> Result<Self::Handle, VarError> {
+        VarHandle::resolve(var_header)
+    }
+
+    fn read(handle: &Self::Handle, data: &[u8]) -> Option<Self> {
+        handle.read(data)
+    }
+}
+
+impl<T: VarScalar> TelemetryField for Vec<T> {
+    type Handle = VarHandle<T>;
+
+    fn bind(var_header: &VarHeader<'static>) -> Result<Self::Handle, VarError> {
+        VarHandle::resolve(var_header)
+    }
+
+    fn read(handle: &Self::Handle, data: &[u8]) -> Option<Self> {
+        Some(handle.read_slice(data)?.to_vec())
+    }
+}
+
+impl TelemetryField for SessionTime {
+    type Handle = VarHeader<'static>;
+
+    fn bind(var_header: &VarHeader<'static>) -> Result<Self::Handle, VarError> {
+        if !var_header.is_time() || var_header.count != 1 {
+            return Err(VarError::TypeMismatch {
+                name: var_header.name.to_string(),
+                expected: "time",
+                found: "non-time var",
+            });
+        }
+        Ok(var_header.clone())
+    }
+
+    fn read(handle: &Self::Handle, data: &[u8]) -> Option<Self> {
+        VarValue::read(data, handle, handle.offset as usize)?.into_time(handle).ok()
+    }
+}
+
+impl TelemetryField for Duration {
+    type Handle = VarHeader<'static>;
+
+    fn bind(var_header: &VarHeader<'static>) -> Result<Self::Handle, VarError> {
+        SessionTime::bind(var_header)
+    }
+
+    /// `None` for negative times, such as `-1` before the first lap.
+    fn read(handle: &Self::Handle, data: &[u8]) -> Option<Self> {
+        SessionTime::read(handle, data)?.to_duration()
+    }
+}
+
+/// Binds one field; used by the derive.
+#[doc(hidden)]
+pub fn bind_field<T: TelemetryField>(
+    var_headers: &HashMap<String, VarHeader<'static>>,
+    name: &str,
+) -> Result<T::Handle, VarError> {
+    let var_header = var_headers.get(name).ok_or_else(|| VarError::UnknownVar(name.to_string()))?;
+    T::bind(var_header)
+}
+
+/// Reads one field; used by the derive.
+#[doc(hidden)]
+pub fn read_field<T: TelemetryField>(handle: &T::Handle, data: &[u8], name: &str) -> Result<T, VarError> {
+    T::read(handle, data).ok_or_else(|| VarError::NoData(name.to_string()))
+}
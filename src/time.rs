@@ -0,0 +1,68 @@
+use std::fmt;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// Seconds as the sim reports them (session time, lap times, time remaining).
+/// May be negative, e.g. `-1` for "no lap time yet". Displays as `1:32.456`,
+/// or `1:02:03.456` past an hour. Read one with `get_time`, which only
+/// accepts vars the sim flags as times.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SessionTime(f64);
+
+impl SessionTime {
+    pub fn from_secs_f64(secs: f64) -> Self {
+        SessionTime(secs)
+    }
+
+    pub fn as_secs_f64(self) -> f64 {
+        self.0
+    }
+
+    /// `None` for negative or non-finite times.
+    pub fn to_duration(self) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.0).ok()
+    }
+}
+
+impl From<Duration> for SessionTime {
+    fn from(duration: Duration) -> Self {
+        SessionTime(duration.as_secs_f64())
+    }
+}
+
+impl fmt::Display for SessionTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let millis = (self.0.abs() * 1000.0).round() as u64;
+        let sign = if self.0 < 0.0 && millis > 0 { "-" } else { "" };
+        let hours = millis / 3_600_000;
+        let minutes = millis / 60_000 % 60;
+        let seconds = millis / 1000 % 60;
+        let millis = millis % 1000;
+        if hours > 0 {
+            write!(f, "{}{}:{:02}:{:02}.{:03}", sign, hours, minutes, seconds, millis)
+        } else {
+            write!(f, "{}{}:{:02}.{:03}", sign, minutes, seconds, millis)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn displays_like_the_sim() {
+        assert_eq!(SessionTime::from_secs_f64(92.456).to_string(), "1:32.456");
+        assert_eq!(SessionTime::from_secs_f64(3723.5).to_string(), "1:02:03.500");
+        assert_eq!(SessionTime::from_secs_f64(-1.0).to_string(), "-0:01.000");
+        assert_eq!(SessionTime::from_secs_f64(-0.0001).to_string(), "0:00.000");
+    }
+
+    #[test]
+    fn converts_to_duration() {
+        assert_eq!(SessionTime::from_secs_f64(3723.5).to_duration(), Some(Duration::from_millis(3_723_500)));
+        assert_eq!(SessionTime::from_secs_f64(-1.0).to_duration(), None);
+        assert_eq!(SessionTime::from(Duration::from_millis(1500)).as_secs_f64(), 1.5);
+    }
+}
@@ -0,0 +1,244 @@
+use std::f64::consts::PI;
+use std::fmt;
+
+/// The physical unit of a var, parsed from `VarHeader::unit`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Unit {
+    None,
+    MetersPerSecond,
+    KilometersPerHour,
+    MilesPerHour,
+    MetersPerSecondSquared,
+    RevsPerMinute,
+    Pascals,
+    KiloPascals,
+    Bar,
+    Psi,
+    Celsius,
+    Fahrenheit,
+    Percent,
+    Radians,
+    Degrees,
+    RadiansPerSecond,
+    Meters,
+    Kilometers,
+    Millimeters,
+    Centimeters,
+    Inches,
+    Seconds,
+    Kilograms,
+    Liters,
+    Volts,
+    Newtons,
+    NewtonMeters,
+    /// Spring rates in car setups.
+    NewtonsPerMillimeter,
+    PoundsPerInch,
+    /// Anything else, including the `irsdk_*` enum and bitfield markers.
+    Other(String),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Dimension {
+    Speed,
+    Pressure,
+    Temperature,
+    Angle,
+    Length,
+    SpringRate,
+}
+
+impl Unit {
+    pub fn parse(unit: &str) -> Unit {
+        match unit.trim() {
+            "" => Unit::None,
+            "m/s" => Unit::MetersPerSecond,
+            "km/h" => Unit::KilometersPerHour,
+            "mph" => Unit::MilesPerHour,
+            "m/s^2" => Unit::MetersPerSecondSquared,
+            "revs/min" => Unit::RevsPerMinute,
+            "Pa" => Unit::Pascals,
+            "kPa" => Unit::KiloPascals,
+            "bar" => Unit::Bar,
+            "psi" => Unit::Psi,
+            "C" => Unit::Celsius,
+            "F" => Unit::Fahrenheit,
+            "%" => Unit::Percent,
+            "rad" => Unit::Radians,
+            "deg" => Unit::Degrees,
+            "rad/s" => Unit::RadiansPerSecond,
+            "m" => Unit::Meters,
+            "km" => Unit::Kilometers,
+            "mm" => Unit::Millimeters,
+            "cm" => Unit::Centimeters,
+            "in" | "\"" => Unit::Inches,
+            "s" => Unit::Seconds,
+            "kg" => Unit::Kilograms,
+            "l" | "L" => Unit::Liters,
+            "V" => Unit::Volts,
+            "N" => Unit::Newtons,
+            "N*m" => Unit::NewtonMeters,
+            "N/mm" => Unit::NewtonsPerMillimeter,
+            "lbs/in" => Unit::PoundsPerInch,
+            other => Unit::Other(other.to_string()),
+        }
+    }
+
+    pub fn symbol(&self) -> &str {
+        match self {
+            Unit::None => "",
+            Unit::MetersPerSecond => "m/s",
+            Unit::KilometersPerHour => "km/h",
+            Unit::MilesPerHour => "mph",
+            Unit::MetersPerSecondSquared => "m/s^2",
+            Unit::RevsPerMinute => "revs/min",
+            Unit::Pascals => "Pa",
+            Unit::KiloPascals => "kPa",
+            Unit::Bar => "bar",
+            Unit::Psi => "psi",
+            Unit::Celsius => "C",
+            Unit::Fahrenheit => "F",
+            Unit::Percent => "%",
+            Unit::Radians => "rad",
+            Unit::Degrees => "deg",
+            Unit::RadiansPerSecond => "rad/s",
+            Unit::Meters => "m",
+            Unit::Kilometers => "km",
+            Unit::Millimeters => "mm",
+            Unit::Centimeters => "cm",
+            Unit::Inches => "in",
+            Unit::Seconds => "s",
+            Unit::Kilograms => "kg",
+            Unit::Liters => "l",
+            Unit::Volts => "V",
+            Unit::Newtons => "N",
+            Unit::NewtonMeters => "N*m",
+            Unit::NewtonsPerMillimeter => "N/mm",
+            Unit::PoundsPerInch => "lbs/in",
+            Unit::Other(other) => other,
+        }
+    }
+
+    // (dimension, factor, offset) such that `base = value * factor + offset`.
+    fn scale(&self) -> Option<(Dimension, f64, f64)> {
+        Some(match self {
+            Unit::MetersPerSecond => (Dimension::Speed, 1.0, 0.0),
+            Unit::KilometersPerHour => (Dimension::Speed, 1.0 / 3.6, 0.0),
+            Unit::MilesPerHour => (Dimension::Speed, 0.44704, 0.0),
+            Unit::Pascals => (Dimension::Pressure, 1.0, 0.0),
+            Unit::KiloPascals => (Dimension::Pressure, 1000.0, 0.0),
+            Unit::Bar => (Dimension::Pressure, 100_000.0, 0.0),
+            Unit::Psi => (Dimension::Pressure, 6894.757293168, 0.0),
+            Unit::Celsius => (Dimension::Temperature, 1.0, 0.0),
+            Unit::Fahrenheit => (Dimension::Temperature, 5.0 / 9.0, -160.0 / 9.0),
+            Unit::Radians => (Dimension::Angle, 1.0, 0.0),
+            Unit::Degrees => (Dimension::Angle, PI / 180.0, 0.0),
+            Unit::Meters => (Dimension::Length, 1.0, 0.0),
+            Unit::Kilometers => (Dimension::Length, 1000.0, 0.0),
+            Unit::Millimeters => (Dimension::Length, 0.001, 0.0),
+            Unit::Centimeters => (Dimension::Length, 0.01, 0.0),
+            Unit::Inches => (Dimension::Length, 0.0254, 0.0),
+            Unit::NewtonsPerMillimeter => (Dimension::SpringRate, 1000.0, 0.0),
+            Unit::PoundsPerInch => (Dimension::SpringRate, 175.126835, 0.0),
+            _ => return None,
+        })
+    }
+
+    /// Converts `value` from this unit to `to`; `None` if they measure
+    /// different things.
+    pub fn convert(&self, value: f64, to: &Unit) -> Option<f64> {
+        if self == to {
+            return Some(value);
+        }
+        let (from_dimension, from_factor, from_offset) = self.scale()?;
+        let (to_dimension, to_factor, to_offset) = to.scale()?;
+        if from_dimension != to_dimension {
+            return None;
+        }
+        Some((value * from_factor + from_offset - to_offset) / to_factor)
+    }
+}
+
+impl From<&str> for Unit {
+    fn from(unit: &str) -> Self {
+        Unit::parse(unit)
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// A numeric var value together with its unit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Quantity { value, unit }
+    }
+
+    pub fn to(&self, unit: Unit) -> Option<Quantity> {
+        let value = self.unit.convert(self.value, &unit)?;
+        Some(Quantity { value, unit })
+    }
+
+    pub fn kph(&self) -> Option<f64> {
+        self.unit.convert(self.value, &Unit::KilometersPerHour)
+    }
+
+    pub fn mph(&self) -> Option<f64> {
+        self.unit.convert(self.value, &Unit::MilesPerHour)
+    }
+
+    pub fn psi(&self) -> Option<f64> {
+        self.unit.convert(self.value, &Unit::Psi)
+    }
+
+    pub fn fahrenheit(&self) -> Option<f64> {
+        self.unit.convert(self.value, &Unit::Fahrenheit)
+    }
+
+    pub fn degrees(&self) -> Option<f64> {
+        self.unit.convert(self.value, &Unit::Degrees)
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.unit {
+            Unit::None => write!(f, "{}", self.value),
+            _ => write!(f, "{} {}", self.value, self.unit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_within_a_dimension() {
+        assert!((Unit::KiloPascals.convert(200.0, &Unit::Psi).unwrap() - 29.0075).abs() < 1e-3);
+        assert!((Unit::parse("C").convert(100.0, &Unit::Fahrenheit).unwrap() - 212.0).abs() < 1e-9);
+        assert!((Unit::Fahrenheit.convert(32.0, &Unit::Celsius).unwrap()).abs() < 1e-9);
+        assert!((Unit::NewtonsPerMillimeter.convert(105.0, &Unit::PoundsPerInch).unwrap() - 599.567).abs() < 0.01);
+        assert_eq!(Unit::MetersPerSecond.convert(1.0, &Unit::Psi), None);
+        assert_eq!(Unit::parse("irsdk_Flags").convert(1.0, &Unit::parse("irsdk_Flags")), Some(1.0));
+    }
+
+    #[test]
+    fn quantity_helpers() {
+        let speed = Quantity::new(25.0, Unit::MetersPerSecond);
+        assert_eq!(speed.kph(), Some(90.0));
+        assert!((speed.mph().unwrap() - 55.923).abs() < 1e-3);
+        assert_eq!(speed.psi(), None);
+        assert_eq!(speed.to_string(), "25 m/s");
+        assert_eq!(Quantity::new(3.0, Unit::None).to_string(), "3");
+    }
+}
@@ -0,0 +1,289 @@
+use std::fmt;
+use crate::constants::*;
+use crate::structs::VarHeader;
+use crate::time::SessionTime;
+use crate::units::{Quantity, Unit};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VarType {
+    Char = 0,
+    Bool = 1,
+    Int = 2,
+    BitField = 3,
+    Float = 4,
+    Double = 5,
+}
+
+impl VarType {
+    pub fn size(self) -> usize {
+        match self {
+            VarType::Char | VarType::Bool => 1,
+            VarType::Int | VarType::BitField | VarType::Float => 4,
+            VarType::Double => 8,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        VAR_TYPE_MAP[self as usize]
+    }
+}
+
+impl TryFrom<i32> for VarType {
+    type Error = i32;
+
+    fn try_from(var_type: i32) -> Result<Self, Self::Error> {
+        match var_type {
+            0 => Ok(VarType::Char),
+            1 => Ok(VarType::Bool),
+            2 => Ok(VarType::Int),
+            3 => Ok(VarType::BitField),
+            4 => Ok(VarType::Float),
+            5 => Ok(VarType::Double),
+            _ => Err(var_type),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VarValue {
+    Char(i8),
+    Bool(bool),
+    Int(i32),
+    BitField(u32),
+    Float(f32),
+    Double(f64),
+    CharArray(Vec<i8>),
+    BoolArray(Vec<bool>),
+    IntArray(Vec<i32>),
+    BitFieldArray(Vec<u32>),
+    FloatArray(Vec<f32>),
+    DoubleArray(Vec<f64>),
+}
+
+#[derive(Debug)]
+pub enum VarError {
+    UnknownVar(String),
+    NoData(String),
+    TypeMismatch { name: String, expected: &'static str, found: &'static str },
+    IndexOutOfRange { name: String, index: usize, count: usize },
+}
+
+impl fmt::Display for VarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarError::UnknownVar(name) => write!(f, "unknown var {}", name),
+            VarError::NoData(what) => write!(f, "no data for {}", what),
+            VarError::TypeMismatch { name, expected, found } => {
+                write!(f, "{} is {}, not {}", name, found, expected)
+            }
+            VarError::IndexOutOfRange { name, index, count } => {
+                write!(f, "index {} out of range for {} with {} elements", index, name, count)
+            }
+        }
+    }
+}
+
+impl std::error::Error for VarError {}
+
+impl VarValue {
+    /// Decodes `var_header` from `mem`, where `offset` points at the first element.
+    pub fn read(mem: &[u8], var_header: &VarHeader<'_>, offset: usize) -> Option<VarValue> {
+        let var_type = VarType::try_from(var_header.var_type).ok()?;
+        let count = usize::try_from(var_header.count).ok()?;
+        let bytes = mem.get(offset..offset.checked_add(var_type.size() * count)?)?;
+        let chunks = bytes.chunks_exact(var_type.size());
+        if count == 1 {
+            return Some(match var_type {
+                VarType::Char => VarValue::Char(bytes[0] as i8),
+                VarType::Bool => VarValue::Bool(bytes[0] != 0),
+                VarType::Int => VarValue::Int(i32::from_le_bytes(bytes.try_into().ok()?)),
+                VarType::BitField => VarValue::BitField(u32::from_le_bytes(bytes.try_into().ok()?)),
+                VarType::Float => VarValue::Float(f32::from_le_bytes(bytes.try_into().ok()?)),
+                VarType::Double => VarValue::Double(f64::from_le_bytes(bytes.try_into().ok()?)),
+            });
+        }
+        Some(match var_type {
+            VarType::Char => VarValue::CharArray(bytes.iter().map(|&b| b as i8).collect()),
+            VarType::Bool => VarValue::BoolArray(bytes.iter().map(|&b| b != 0).collect()),
+            VarType::Int => VarValue::IntArray(chunks.map(|c| i32::from_le_bytes(c.try_into().unwrap())).collect()),
+            VarType::BitField => VarValue::BitFieldArray(chunks.map(|c| u32::from_le_bytes(c.try_into().unwrap())).collect()),
+            VarType::Float => VarValue::FloatArray(chunks.map(|c| f32::from_le_bytes(c.try_into().unwrap())).collect()),
+            VarType::Double => VarValue::DoubleArray(chunks.map(|c| f64::from_le_bytes(c.try_into().unwrap())).collect()),
+        })
+    }
+
+    pub fn var_type(&self) -> VarType {
+        match self {
+            VarValue::Char(_) | VarValue::CharArray(_) => VarType::Char,
+            VarValue::Bool(_) | VarValue::BoolArray(_) => VarType::Bool,
+            VarValue::Int(_) | VarValue::IntArray(_) => VarType::Int,
+            VarValue::BitField(_) | VarValue::BitFieldArray(_) => VarType::BitField,
+            VarValue::Float(_) | VarValue::FloatArray(_) => VarType::Float,
+            VarValue::Double(_) | VarValue::DoubleArray(_) => VarType::Double,
+        }
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self,
+            VarValue::CharArray(_)
+                | VarValue::BoolArray(_)
+                | VarValue::IntArray(_)
+                | VarValue::BitFieldArray(_)
+                | VarValue::FloatArray(_)
+                | VarValue::DoubleArray(_)
+        )
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match (self.var_type(), self.is_array()) {
+            (VarType::Char, true) => "[i8]",
+            (VarType::Bool, true) => "[bool]",
+            (VarType::Int, true) => "[i32]",
+            (VarType::BitField, true) => "[u32]",
+            (VarType::Float, true) => "[f32]",
+            (VarType::Double, true) => "[f64]",
+            (var_type, false) => var_type.name(),
+        }
+    }
+
+    /// Numeric scalars as `f64`; `None` for bools and arrays.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            VarValue::Char(v) => Some(*v as f64),
+            VarValue::Int(v) => Some(*v as f64),
+            VarValue::BitField(v) => Some(*v as f64),
+            VarValue::Float(v) => Some(*v as f64),
+            VarValue::Double(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn into_quantity(self, name: &str, unit: &str) -> Result<Quantity, VarError> {
+        let value = self.as_f64().ok_or_else(|| VarError::TypeMismatch {
+            name: name.to_string(),
+            expected: "number",
+            found: self.type_name(),
+        })?;
+        Ok(Quantity::new(value, Unit::parse(unit)))
+    }
+
+    pub fn into_time(self, var_header: &VarHeader<'_>) -> Result<SessionTime, VarError> {
+        let value = self.as_f64().filter(|_| var_header.is_time());
+        value.map(SessionTime::from_secs_f64).ok_or_else(|| VarError::TypeMismatch {
+            name: var_header.name.to_string(),
+            expected: "time",
+            found: self.type_name(),
+        })
+    }
+
+    pub fn into_typed<T: FromVarValue>(self, name: &str) -> Result<T, VarError> {
+        let found = self.type_name();
+        T::from_var_value(self).ok_or_else(|| VarError::TypeMismatch {
+            name: name.to_string(),
+            expected: T::TYPE_NAME,
+            found,
+        })
+    }
+}
+
+fn fmt_array<T: fmt::Display>(f: &mut fmt::Formatter<'_>, values: &[T]) -> fmt::Result {
+    write!(f, "[")?;
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    write!(f, "]")
+}
+
+impl fmt::Display for VarValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VarValue::Char(v) => write!(f, "{}", v),
+            VarValue::Bool(v) => write!(f, "{}", v),
+            VarValue::Int(v) => write!(f, "{}", v),
+            VarValue::BitField(v) => write!(f, "{}", v),
+            VarValue::Float(v) => write!(f, "{}", v),
+            VarValue::Double(v) => write!(f, "{}", v),
+            VarValue::CharArray(v) => fmt_array(f, v),
+            VarValue::BoolArray(v) => fmt_array(f, v),
+            VarValue::IntArray(v) => fmt_array(f, v),
+            VarValue::BitFieldArray(v) => fmt_array(f, v),
+            VarValue::FloatArray(v) => fmt_array(f, v),
+            VarValue::DoubleArray(v) => fmt_array(f, v),
+        }
+    }
+}
+
+pub trait FromVarValue: Sized {
+    const TYPE_NAME: &'static str;
+
+    fn from_var_value(value: VarValue) -> Option<Self>;
+}
+
+macro_rules! impl_from_var_value {
+    ($ty:ty, $name:expr, $scalar:ident, $array:ident) => {
+        impl FromVarValue for $ty {
+            const TYPE_NAME: &'static str = $name;
+
+            fn from_var_value(value: VarValue) -> Option<Self> {
+                match value {
+                    VarValue::$scalar(v) => Some(v),
+                    _ => None,
+                }
+            }
+        }
+
+        impl FromVarValue for Vec<$ty> {
+            const TYPE_NAME: &'static str = concat!("[", $name, "]");
+
+            fn from_var_value(value: VarValue) -> Option<Self> {
+                match value {
+                    VarValue::$array(v) => Some(v),
+                    VarValue::$scalar(v) => Some(vec![v]),
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+impl_from_var_value!(i8, "i8", Char, CharArray);
+impl_from_var_value!(bool, "bool", Bool, BoolArray);
+impl_from_var_value!(i32, "i32", Int, IntArray);
+impl_from_var_value!(u32, "u32", BitField, BitFieldArray);
+impl_from_var_value!(f32, "f32", Float, FloatArray);
+impl_from_var_value!(f64, "f64", Double, DoubleArray);
+
+/// Named accessors for vars that have a dedicated type, e.g. `session_flags()`.
+/// `mut` expands to `&mut self` receivers (for `IRSDK`), `ref` to `&self`.
+macro_rules! typed_var_accessors {
+    (@accessor mut $name:ident, $ty:ty, $var:literal) => {
+        pub fn $name(&mut self) -> Result<$ty, $crate::value::VarError> {
+            self.get_as::<$ty>($var)
+        }
+    };
+    (@accessor ref $name:ident, $ty:ty, $var:literal) => {
+        pub fn $name(&self) -> Result<$ty, $crate::value::VarError> {
+            self.get_as::<$ty>($var)
+        }
+    };
+    ($mode:ident) => {
+        typed_var_accessors!(@accessor $mode session_flags, $crate::bitfields::SessionFlags, "SessionFlags");
+        typed_var_accessors!(@accessor $mode engine_warnings, $crate::bitfields::EngineWarnings, "EngineWarnings");
+        typed_var_accessors!(@accessor $mode cam_camera_state, $crate::bitfields::CameraState, "CamCameraState");
+        typed_var_accessors!(@accessor $mode pit_sv_flags, $crate::bitfields::PitSvFlags, "PitSvFlags");
+        typed_var_accessors!(@accessor $mode pace_flags, $crate::bitfields::PaceFlags, "PaceFlags");
+        typed_var_accessors!(@accessor $mode player_track_surface, $crate::enums::TrkLoc, "PlayerTrackSurface");
+        typed_var_accessors!(@accessor $mode player_track_surface_material, $crate::enums::TrkSurf, "PlayerTrackSurfaceMaterial");
+        typed_var_accessors!(@accessor $mode session_state, $crate::enums::SessionState, "SessionState");
+        typed_var_accessors!(@accessor $mode pit_sv_status, $crate::enums::PitSvStatus, "PlayerCarPitSvStatus");
+        typed_var_accessors!(@accessor $mode pace_mode, $crate::enums::PaceMode, "PaceMode");
+        typed_var_accessors!(@accessor $mode car_left_right, $crate::enums::CarLeftRight, "CarLeftRight");
+        typed_var_accessors!(@accessor $mode track_wetness, $crate::enums::TrackWetness, "TrackWetness");
+    };
+}
+
+pub(crate) use typed_var_accessors;
@@ -0,0 +1,566 @@
+use std::path::PathBuf;
+use std::time::Duration;
+use irsdk::irsdk::IRSDKError;
+use irsdk::{ConnectionState, Header, SessionArchive, SessionInfoChange, Telemetry, TelemetrySource, FakeSim, FileSource, ParseError, SessionFlags, SessionState, SessionTime, TrkLoc, Unit, VarError, VarType, VarValue, IRSDK};
+
+const SESSION_INFO: &str = "---
+WeekendInfo:
+ TrackName: spa 2024 gp
+ TrackID: 524
+
+DriverInfo:
+ DriverCarIdx: 1
+ Drivers:
+ - CarIdx: 0
+   UserName: Pace Car
+ - CarIdx: 1
+   UserName: Jane Doe
+
+...
+";
+
+fn fake_sim() -> FakeSim {
+    let mut sim = FakeSim::new();
+    sim.add_var("SessionTime", VarType::Double, 1, "s", "Seconds since session start")
+        .add_var("Speed", VarType::Float, 1, "m/s", "GPS vehicle speed")
+        .add_var("Gear", VarType::Int, 1, "", "Current gear")
+        .add_var("OnPitRoad", VarType::Bool, 1, "", "Is the player car on pit road")
+        .add_var("SessionFlags", VarType::BitField, 1, "irsdk_Flags", "Session flags")
+        .add_var("SessionState", VarType::Int, 1, "irsdk_SessionState", "Session state")
+        .add_var("CarIdxTrackSurface", VarType::Int, 4, "irsdk_TrkLoc", "Track surface type by car index")
+        .add_var("CarIdxLapDistPct", VarType::Float, 4, "%", "Percentage distance around lap by car index");
+    sim.set_session_info(SESSION_INFO);
+    sim
+}
+
+// A fake sim with `values` set and ticked into both of the newest buffers,
+// so frozen reads and unfrozen ones (from the second newest) agree.
+fn sim_with(values: &[(&str, VarValue)]) -> FakeSim {
+    let mut sim = fake_sim();
+    set_and_tick(&mut sim, values);
+    sim
+}
+
+fn set_and_tick(sim: &mut FakeSim, values: &[(&str, VarValue)]) {
+    for (name, value) in values {
+        sim.set(name, value.clone()).unwrap();
+    }
+    sim.tick();
+    sim.tick();
+}
+
+fn connect(sim: FakeSim) -> IRSDK<FakeSim> {
+    let mut ir = IRSDK::with_source(false, Some(sim));
+    assert!(ir.startup(None, None).unwrap());
+    ir
+}
+
+fn temp_path(name: &str, extension: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("irsdk_{}_{}.{}", name, std::process::id(), extension))
+}
+
+fn user_name<S: TelemetrySource>(ir: &mut IRSDK<S>, car_idx: i64) -> Option<String> {
+    let query = format!("DriverInfo:Drivers:CarIdx:{{{}}}UserName:", car_idx);
+    ir.session_query(&query).unwrap().and_then(|v| v.as_str()).map(str::to_string)
+}
+
+#[test]
+fn reads_vars_from_latest_tick() {
+    let mut ir = connect(sim_with(&[
+        ("SessionTime", VarValue::Double(12.5)),
+        ("Speed", VarValue::Float(42.0)),
+        ("Gear", VarValue::Int(3)),
+        ("OnPitRoad", VarValue::Bool(true)),
+        ("SessionFlags", VarValue::BitField(0x4)),
+        ("CarIdxLapDistPct", VarValue::FloatArray(vec![0.1, 0.2, 0.3, 0.4])),
+    ]));
+    assert!(ir.is_connected());
+
+    ir.freeze_var_buffer_latest();
+    assert_eq!(ir.get_as::<f64>("SessionTime").unwrap(), 12.5);
+    assert_eq!(ir.get_as::<f32>("Speed").unwrap(), 42.0);
+    assert_eq!(ir.get_as::<i32>("Gear").unwrap(), 3);
+    assert!(ir.get_as::<bool>("OnPitRoad").unwrap());
+    assert_eq!(ir.get_as::<u32>("SessionFlags").unwrap(), 0x4);
+    assert_eq!(ir.get("CarIdxLapDistPct"), Some(VarValue::FloatArray(vec![0.1, 0.2, 0.3, 0.4])));
+    assert!(matches!(ir.get_as::<i32>("Speed"), Err(VarError::TypeMismatch { .. })));
+    assert!(matches!(ir.get_as::<f32>("RPM"), Err(VarError::UnknownVar(_))));
+
+    ir.source_mut().unwrap().set("Gear", VarValue::Int(4)).unwrap();
+    ir.source_mut().unwrap().tick();
+    ir.freeze_var_buffer_latest();
+    assert_eq!(ir.get_as::<i32>("Gear").unwrap(), 4);
+}
+
+#[test]
+fn exposes_session_info() {
+    let mut ir = connect(fake_sim());
+    let session_info = ir.session_info().unwrap();
+    assert_eq!(session_info.weekend_info.as_ref().unwrap().track_id, Some(524));
+    assert_eq!(user_name(&mut ir, 1).as_deref(), Some("Jane Doe"));
+
+    ir.source_mut().unwrap().set_session_info(&SESSION_INFO.replace("Jane Doe", "John Roe"));
+    assert_eq!(user_name(&mut ir, 1).as_deref(), Some("John Roe"));
+}
+
+#[test]
+fn keeps_serving_session_info_after_parse_error() {
+    let mut ir = connect(fake_sim());
+    assert!(ir.session_info_error().is_none());
+
+    ir.source_mut().unwrap().set_session_info("---\nWeekendInfo:\n  TrackName: spa\n TrackID: 524\n\n...\n");
+    assert!(ir.session_info_error().is_some());
+    assert!(ir.session_info_error().is_some());
+    assert_eq!(ir.parsed_session_info().unwrap().update, 1);
+    assert_eq!(user_name(&mut ir, 1).as_deref(), Some("Jane Doe"));
+
+    ir.source_mut().unwrap().set_session_info(&SESSION_INFO.replace("Jane Doe", "John Roe"));
+    assert!(ir.session_info_error().is_none());
+    assert_eq!(ir.parsed_session_info().unwrap().update, 3);
+}
+
+#[test]
+fn reads_dump_file() {
+    let path = temp_path("fake_sim", "bin");
+    sim_with(&[("Speed", VarValue::Float(7.5))]).write_to(path.to_str().unwrap()).unwrap();
+
+    let mut ir = IRSDK::<FileSource>::with_source(false, None);
+    assert!(ir.startup(path.to_str(), None).unwrap());
+    ir.freeze_var_buffer_latest();
+    assert_eq!(ir.get_as::<f32>("Speed").unwrap(), 7.5);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn tracks_connection_state() {
+    let mut ir = IRSDK::with_source(false, Some(fake_sim()));
+    assert_eq!(ir.connection_state(), ConnectionState::Disconnected);
+    ir.startup(None, None).unwrap();
+    assert_eq!(ir.connection_state(), ConnectionState::Connected);
+
+    ir.set_stale_timeout(Duration::ZERO);
+    assert_eq!(ir.update_connection(), ConnectionState::Stale);
+    ir.source_mut().unwrap().tick();
+    assert_eq!(ir.update_connection(), ConnectionState::Connected);
+
+    ir.source_mut().unwrap().set_connected(false);
+    assert_eq!(ir.update_connection(), ConnectionState::WaitingForSim);
+    ir.set_reconnect_interval(Duration::ZERO);
+    assert_eq!(ir.update_connection(), ConnectionState::WaitingForSim);
+
+    let sim = ir.source_mut().unwrap();
+    sim.set_connected(true);
+    set_and_tick(sim, &[("Speed", VarValue::Float(31.5))]);
+    assert_eq!(ir.update_connection(), ConnectionState::Connected);
+    ir.freeze_var_buffer_latest();
+    assert_eq!(ir.get_as::<f32>("Speed").unwrap(), 31.5);
+    assert_eq!(ir.session_info().and_then(|info| info.weekend_info.as_ref()?.track_id), Some(524));
+
+    ir.shutdown();
+    let transitions: Vec<_> = ir.take_connection_events().iter().map(|e| e.to).collect();
+    assert_eq!(
+        transitions,
+        [
+            ConnectionState::Connected,
+            ConnectionState::Stale,
+            ConnectionState::Connected,
+            ConnectionState::WaitingForSim,
+            ConnectionState::Connected,
+            ConnectionState::Disconnected,
+        ]
+    );
+    assert!(ir.take_connection_events().is_empty());
+}
+
+#[test]
+fn yields_one_frame_per_tick() {
+    let mut ir = connect(sim_with(&[("Speed", VarValue::Float(10.0))]));
+
+    let mut frames = ir.frames();
+    let first = frames.next().unwrap();
+    let sim = frames.irsdk_mut().source_mut().unwrap();
+    sim.set("Speed", VarValue::Float(20.0)).unwrap();
+    sim.tick();
+    let second = frames.next().unwrap();
+
+    assert_eq!(second.tick_count(), first.tick_count() + 1);
+    assert_eq!(first.get_as::<f32>("Speed").unwrap(), 10.0);
+    assert_eq!(second.get_as::<f32>("Speed").unwrap(), 20.0);
+    assert!(matches!(second.get_as::<f32>("RPM"), Err(VarError::UnknownVar(_))));
+}
+
+#[tokio::test]
+async fn streams_frames_from_dump() {
+    let path = temp_path("fake_sim_stream", "bin");
+    sim_with(&[("Gear", VarValue::Int(5))]).write_to(path.to_str().unwrap()).unwrap();
+
+    let mut ir = IRSDK::<FileSource>::with_source(false, None);
+    ir.startup(path.to_str(), None).unwrap();
+    let mut stream = ir.frame_stream();
+    let frame = stream.recv().await.unwrap();
+    assert_eq!(frame.get_as::<i32>("Gear").unwrap(), 5);
+    assert!(stream.recv().await.is_none());
+    std::fs::remove_file(path).unwrap();
+}
+
+// A fake sim that reports when it is dropped, i.e. when whoever owned the
+// `IRSDK` has let go of it.
+struct DropSignal {
+    sim: FakeSim,
+    dropped: std::sync::mpsc::Sender<()>,
+}
+
+impl TelemetrySource for DropSignal {
+    fn open(_test_file: Option<&str>) -> Result<Self, IRSDKError> {
+        Err(IRSDKError::ConnectionFailed("pass a DropSignal to with_source".to_string()))
+    }
+
+    fn memory(&self) -> &[u8] {
+        self.sim.memory()
+    }
+
+    fn wait_for_data(&self, timeout_ms: u32) -> bool {
+        self.sim.wait_for_data(timeout_ms)
+    }
+
+    fn is_live(&self) -> bool {
+        self.sim.is_live()
+    }
+}
+
+impl Drop for DropSignal {
+    fn drop(&mut self) {
+        let _ = self.dropped.send(());
+    }
+}
+
+#[tokio::test]
+async fn stream_reader_exits_when_dropped_while_paused() {
+    let (dropped, on_drop) = std::sync::mpsc::channel();
+    let source = DropSignal { sim: sim_with(&[("Gear", VarValue::Int(2))]), dropped };
+    let mut ir = IRSDK::with_source(false, Some(source));
+    ir.startup(None, None).unwrap();
+
+    // The sim never ticks again, so the reader thread waits for a new tick.
+    let mut stream = ir.frame_stream();
+    assert_eq!(stream.recv().await.unwrap().get_as::<i32>("Gear").unwrap(), 2);
+    std::thread::sleep(Duration::from_millis(50));
+    drop(stream);
+    assert!(on_drop.recv_timeout(Duration::from_secs(5)).is_ok());
+}
+
+#[test]
+fn snapshot_carries_tick_and_session_time() {
+    let mut ir = connect(sim_with(&[("SessionTime", VarValue::Double(61.25))]));
+    let frame = ir.snapshot().unwrap();
+    assert_eq!(frame.tick_count(), 2);
+    assert_eq!(frame.session_time(), Some(SessionTime::from_secs_f64(61.25)));
+}
+
+#[test]
+fn rejects_truncated_dump() {
+    let sim = sim_with(&[]);
+    let path = temp_path("fake_sim_truncated", "bin");
+    std::fs::write(&path, &sim.bytes()[..sim.bytes().len() - 8]).unwrap();
+
+    let mut ir = IRSDK::<FileSource>::with_source(false, None);
+    let result = ir.startup(path.to_str(), None);
+    assert!(matches!(
+        result,
+        Err(IRSDKError::ParseError(ParseError::OutOfBounds { what: "var buffer", .. }))
+    ));
+    assert!(!ir.is_connected());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn errors_work_with_box_dyn_error() {
+    fn read_rpm(ir: &mut IRSDK<FakeSim>) -> Result<f32, Box<dyn std::error::Error>> {
+        ir.startup(None, None)?;
+        Ok(ir.get_as::<f32>("RPM")?)
+    }
+    let mut ir = IRSDK::with_source(false, Some(fake_sim()));
+    assert_eq!(read_rpm(&mut ir).unwrap_err().to_string(), "unknown var RPM");
+
+    let error = IRSDKError::from(ParseError::InvalidCount { what: "num_buf", value: 9 });
+    assert_eq!(error.to_string(), "invalid telemetry memory: invalid num_buf: 9");
+    assert!(std::error::Error::source(&error).is_some());
+}
+
+#[test]
+fn reads_through_handles() {
+    let mut ir = connect(sim_with(&[("Speed", VarValue::Float(33.0)), ("OnPitRoad", VarValue::Bool(true))]));
+
+    let speed = ir.handle::<f32>("Speed").unwrap();
+    let on_pit_road = ir.handle::<bool>("OnPitRoad").unwrap();
+    assert_eq!(ir.read(speed), Some(33.0));
+    assert_eq!(ir.read(on_pit_road), Some(true));
+    assert!(matches!(ir.handle::<i32>("Speed"), Err(VarError::TypeMismatch { .. })));
+    assert!(matches!(ir.handle::<f32>("RPM"), Err(VarError::UnknownVar(_))));
+
+    let frame = ir.snapshot().unwrap();
+    assert_eq!(frame.read(speed), Some(33.0));
+}
+
+#[test]
+fn reads_array_elements() {
+    let mut ir = connect(sim_with(&[("CarIdxLapDistPct", VarValue::FloatArray(vec![0.1, 0.2, 0.3, 0.4]))]));
+
+    assert_eq!(ir.get_index::<f32>("CarIdxLapDistPct", 2).unwrap(), 0.3);
+    assert!(matches!(
+        ir.get_index::<f32>("CarIdxLapDistPct", 4),
+        Err(VarError::IndexOutOfRange { index: 4, count: 4, .. })
+    ));
+    let lap_dist = ir.get_slice::<f32>("CarIdxLapDistPct").unwrap();
+    assert_eq!(lap_dist.len(), 4);
+    assert_eq!(lap_dist.get(1), Some(0.2));
+    assert_eq!(lap_dist.get(4), None);
+    assert_eq!(lap_dist.get(usize::MAX), None);
+    assert_eq!(lap_dist.to_vec(), vec![0.1, 0.2, 0.3, 0.4]);
+}
+
+#[test]
+fn reads_session_flags() {
+    let mut ir = connect(sim_with(&[("SessionFlags", VarValue::BitField(0x0004 | 0x0020 | 0x0400_0000))]));
+
+    let frame = ir.snapshot().unwrap();
+    let flags = frame.session_flags().unwrap();
+    assert!(flags.contains(SessionFlags::GREEN | SessionFlags::BLUE));
+    assert!(!flags.contains(SessionFlags::CHECKERED));
+    assert_eq!(flags.iter_names().map(|(name, _)| name).collect::<Vec<_>>(), ["GREEN", "BLUE"]);
+    assert_eq!(flags.to_string(), "GREEN | BLUE | 0x4000000");
+    assert!(matches!(frame.engine_warnings(), Err(VarError::UnknownVar(_))));
+}
+
+#[test]
+fn reads_enum_vars() {
+    let mut ir = connect(sim_with(&[
+        ("SessionState", VarValue::Int(4)),
+        ("CarIdxTrackSurface", VarValue::IntArray(vec![-1, 3, 1, 42])),
+    ]));
+
+    let frame = ir.snapshot().unwrap();
+    let session_state = frame.session_state().unwrap();
+    assert!(session_state.is_racing());
+    assert_eq!(session_state, SessionState::Racing);
+    assert_eq!(session_state.to_string(), "racing");
+
+    let track_surface = frame.get_slice::<TrkLoc>("CarIdxTrackSurface").unwrap().to_vec();
+    assert_eq!(track_surface, [TrkLoc::NotInWorld, TrkLoc::OnTrack, TrkLoc::InPitStall, TrkLoc::Unknown(42)]);
+    assert!(track_surface[2].is_in_pits());
+    assert_eq!(TrkLoc::from(3), TrkLoc::OnTrack);
+    assert_eq!(i32::from(TrkLoc::Unknown(42)), 42);
+}
+
+#[test]
+fn reads_quantities() {
+    let mut ir = connect(sim_with(&[("Speed", VarValue::Float(25.0))]));
+    let speed = ir.snapshot().unwrap().get_quantity("Speed").unwrap();
+    assert_eq!(speed.unit, Unit::MetersPerSecond);
+    assert_eq!(speed.kph(), Some(90.0));
+}
+
+#[test]
+fn reads_time_vars() {
+    let mut sim = fake_sim();
+    sim.add_time_var("LapLastLapTime", VarType::Float, 1, "", "Players last lap time");
+    set_and_tick(&mut sim, &[
+        ("SessionTime", VarValue::Double(3723.5)),
+        ("LapLastLapTime", VarValue::Float(92.456)),
+        ("Speed", VarValue::Float(25.0)),
+    ]);
+    let mut ir = connect(sim);
+    ir.freeze_var_buffer_latest();
+
+    let lap_time = ir.get_time("LapLastLapTime").unwrap();
+    assert_eq!(lap_time.to_string(), "1:32.456");
+    assert!((lap_time.to_duration().unwrap().as_secs_f64() - 92.456).abs() < 1e-3);
+
+    let frame = ir.snapshot().unwrap();
+    assert_eq!(frame.session_time().unwrap().to_string(), "1:02:03.500");
+    assert_eq!(frame.get_time("SessionTime").unwrap().to_duration(), Some(Duration::from_millis(3_723_500)));
+    assert!(matches!(frame.get_time("Speed"), Err(VarError::TypeMismatch { .. })));
+}
+
+#[derive(Debug, Telemetry)]
+struct Car {
+    speed: f32,
+    gear: i32,
+    on_pit_road: bool,
+    session_time: SessionTime,
+    session_state: SessionState,
+    #[irsdk(name = "CarIdxLapDistPct")]
+    lap_dist_pct: Vec<f32>,
+}
+
+#[derive(Telemetry)]
+#[allow(dead_code)]
+struct Engine {
+    #[irsdk(name = "RPM")]
+    rpm: f32,
+}
+
+#[derive(Telemetry)]
+#[allow(dead_code)]
+struct Mistyped {
+    gear: f32,
+}
+
+#[test]
+fn fills_derived_structs() {
+    let mut ir = connect(sim_with(&[
+        ("SessionTime", VarValue::Double(92.456)),
+        ("Speed", VarValue::Float(42.0)),
+        ("Gear", VarValue::Int(3)),
+        ("OnPitRoad", VarValue::Bool(true)),
+        ("SessionState", VarValue::Int(4)),
+        ("CarIdxLapDistPct", VarValue::FloatArray(vec![0.0, 0.25, 0.5, 0.75])),
+    ]));
+
+    let frame = ir.snapshot().unwrap();
+    let handles = frame.bind::<Car>().unwrap();
+    let car: Car = frame.read_telemetry(&handles).unwrap();
+    assert_eq!(car.speed, 42.0);
+    assert_eq!(car.gear, 3);
+    assert!(car.on_pit_road);
+    assert_eq!(car.session_time.to_string(), "1:32.456");
+    assert_eq!(car.session_state, SessionState::Racing);
+    assert_eq!(car.lap_dist_pct, [0.0, 0.25, 0.5, 0.75]);
+
+    ir.freeze_var_buffer_latest();
+    let handles = ir.bind::<Car>().unwrap();
+    assert_eq!(ir.read_telemetry::<Car>(&handles).unwrap().gear, 3);
+
+    assert!(matches!(ir.bind::<Engine>(), Err(VarError::UnknownVar(name)) if name == "RPM"));
+    assert!(matches!(
+        ir.bind::<Mistyped>(),
+        Err(VarError::TypeMismatch { name, expected: "f32", found: "i32" }) if name == "Gear"
+    ));
+}
+
+#[test]
+fn generates_structs_from_var_headers() {
+    let mut sim = fake_sim();
+    sim.add_time_var("LapLastLapTime", VarType::Float, 1, "s", "Players last lap time")
+        .add_var("LFshockDefl", VarType::Float, 1, "m", "LF shock deflection")
+        .add_var("dcBrakeBias", VarType::Float, 1, "", "In car brake bias adjustment");
+    let module = irsdk::codegen::generate_from_bytes(sim.bytes(), "CarVars").unwrap();
+
+    assert!(module.contains("#[derive(Debug, Clone, irsdk::Telemetry)]\npub struct CarVars {"));
+    assert!(module.contains("    /// GPS vehicle speed (`m/s`)\n    #[irsdk(name = \"Speed\")]\n    pub speed: f32,"));
+    assert!(module.contains("    pub session_time: irsdk::SessionTime,"));
+    assert!(module.contains("    pub session_flags: irsdk::SessionFlags,"));
+    assert!(module.contains("    pub car_idx_track_surface: Vec<irsdk::TrkLoc>,"));
+    assert!(module.contains("    pub car_idx_lap_dist_pct: Vec<f32>,"));
+    assert!(module.contains("    pub lap_last_lap_time: irsdk::SessionTime,"));
+    assert!(module.contains("    #[irsdk(name = \"LFshockDefl\")]\n    pub lf_shock_defl: f32,"));
+    assert!(module.contains("    /// In car brake bias adjustment\n    #[irsdk(name = \"dcBrakeBias\")]\n    pub dc_brake_bias: f32,"));
+}
+
+#[test]
+fn decodes_windows_1252_strings() {
+    let mut sim = fake_sim();
+    sim.add_var("TrackTempCrew", VarType::Float, 1, "C", "Temperature of track measured by crew around track (°C)");
+    sim.set_session_info(&SESSION_INFO.replace("Jane Doe", "José Müller"));
+    let raw = sim.bytes();
+    assert!(raw.windows(4).any(|w| w == b"Jos\xe9"));
+
+    let header = Header::parse(raw).unwrap();
+    let var_header = header.var_headers().find(|vh| vh.name == "TrackTempCrew").unwrap();
+    assert_eq!(var_header.desc, "Temperature of track measured by crew around track (°C)");
+
+    let mut ir = connect(sim);
+    assert_eq!(user_name(&mut ir, 1).as_deref(), Some("José Müller"));
+}
+
+#[test]
+fn parses_session_info_on_background_worker() {
+    let mut ir = IRSDK::with_source(true, Some(fake_sim()));
+    ir.startup(None, None).unwrap();
+
+    let mut parsed = None;
+    for _ in 0..200 {
+        parsed = ir.parsed_session_info();
+        if parsed.is_some() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let parsed = parsed.expect("worker never published session info");
+    assert_eq!(parsed.update, 1);
+    assert_eq!(ir.get_session_info("WeekendInfo").unwrap()["TrackID"], 524);
+
+    ir.source_mut().unwrap().set_session_info(&SESSION_INFO.replace("Jane Doe", "John Roe"));
+    // Until the worker catches up, the previous version is still served.
+    assert!(ir.parsed_session_info().unwrap().update >= 1);
+    for _ in 0..200 {
+        if ir.parsed_session_info().unwrap().update == 2 {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(user_name(&mut ir, 1).as_deref(), Some("John Roe"));
+}
+
+#[test]
+fn diffs_every_session_info_bump_seen_by_update_connection() {
+    let mut ir = connect(fake_sim());
+    assert_eq!(ir.update_connection(), ConnectionState::Connected);
+
+    let joined = SESSION_INFO.replace("...", " - CarIdx: 2\n   UserName: Max Mustermann\n\n...");
+    ir.source_mut().unwrap().set_session_info(&joined);
+    ir.update_connection();
+    ir.source_mut().unwrap().set_session_info(SESSION_INFO);
+    ir.update_connection();
+
+    let events = ir.take_session_info_events();
+    let updates: Vec<_> = events.iter().map(|event| (event.from_update, event.to_update)).collect();
+    assert_eq!(updates, [(1, 2), (2, 3)]);
+    assert_eq!(events[0].changes, [SessionInfoChange::DriverJoined { car_idx: 2, user_name: Some("Max Mustermann".to_string()) }]);
+    assert_eq!(events[1].changes, [SessionInfoChange::DriverLeft { car_idx: 2, user_name: Some("Max Mustermann".to_string()) }]);
+}
+
+#[test]
+fn archives_session_info_versions() {
+    let mut sim = fake_sim();
+    sim.add_var("SessionNum", VarType::Int, 1, "", "Session number");
+    set_and_tick(&mut sim, &[("SessionNum", VarValue::Int(1)), ("SessionTime", VarValue::Double(10.0))]);
+    let mut ir = connect(sim);
+    ir.enable_session_archive();
+    assert_eq!(ir.session_archive().unwrap().len(), 1);
+
+    // Recorded from the per-tick path, without any session info accessor.
+    let sim = ir.source_mut().unwrap();
+    set_and_tick(sim, &[("SessionTime", VarValue::Double(95.5))]);
+    sim.set_session_info(&SESSION_INFO.replace("Jane Doe", "José Müller"));
+    ir.update_connection();
+    // The next session restarts SessionTime with the same string.
+    set_and_tick(ir.source_mut().unwrap(), &[("SessionNum", VarValue::Int(2)), ("SessionTime", VarValue::Double(5.0))]);
+    ir.update_connection();
+
+    let archive = ir.take_session_archive().unwrap();
+    let recorded: Vec<_> = archive.entries().iter().map(|entry| (entry.update, entry.session_num, entry.session_time)).collect();
+    assert_eq!(recorded, [(1, Some(1), Some(10.0)), (2, Some(1), Some(95.5)), (2, Some(2), Some(5.0))]);
+
+    let path = temp_path("session_archive", "yaml");
+    archive.save(path.to_str().unwrap()).unwrap();
+    let loaded = SessionArchive::load(path.to_str().unwrap()).unwrap();
+    // Archives saved before versions carried a session number and time.
+    std::fs::write(&path, "entries:\n- update: 1\n  yaml: 'WeekendInfo: {}'\n").unwrap();
+    let old = SessionArchive::load(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!((old.entries()[0].session_num, old.entries()[0].session_time), (None, None));
+    assert_eq!(loaded.entries(), archive.entries());
+    assert_eq!(loaded.last_update(), Some(2));
+    assert_eq!(loaded.last_session_num(), Some(2));
+
+    let user_name = |entry: &irsdk::ArchivedSessionInfo| {
+        let parsed = entry.parse().unwrap();
+        parsed.document.and_then(|doc| doc.driver_info?.drivers?.into_iter().nth(1)?.user_name)
+    };
+    assert_eq!(user_name(loaded.at_session_time(1, 60.0).unwrap()).as_deref(), Some("Jane Doe"));
+    assert_eq!(user_name(loaded.at_session_time(1, 100.0).unwrap()).as_deref(), Some("José Müller"));
+    assert_eq!(user_name(loaded.at_session_time(2, 60.0).unwrap()).as_deref(), Some("José Müller"));
+    assert!(loaded.at_session_time(2, 1.0).is_none());
+    assert!(loaded.at_session_time(3, 60.0).is_none());
+}
@@ -0,0 +1,107 @@
+use irsdk::{VarType, VarValue, IBT};
+
+const SESSION_INFO: &str = "---
+WeekendInfo:
+ TrackName: spa 2024 gp
+ TrackID: 524
+
+...
+";
+
+// (name, type, count, offset in the record)
+const VARS: &[(&str, VarType, usize, usize)] = &[
+    ("SessionTime", VarType::Double, 1, 0),
+    ("Speed", VarType::Float, 1, 8),
+    ("Gear", VarType::Int, 1, 12),
+    ("SessionFlags", VarType::BitField, 1, 16),
+    ("OnPitRoad", VarType::Bool, 1, 20),
+    ("DCDriversSoFar", VarType::Char, 1, 21),
+    ("CarIdxLapDistPct", VarType::Float, 3, 24),
+];
+const RECORD_LEN: usize = 40;
+const RECORD_COUNT: usize = 3;
+
+fn put_i32(file: &mut [u8], offset: usize, value: i32) {
+    file[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+// Header at 0, disk sub-header at 112, then var headers, session info and
+// records, the way the sim writes telemetry files.
+fn ibt_file() -> Vec<u8> {
+    let var_header_offset = 144;
+    let session_info_offset = var_header_offset + VARS.len() * 144;
+    let records_offset = session_info_offset + SESSION_INFO.len() + 1;
+    let mut file = vec![0u8; records_offset + RECORD_COUNT * RECORD_LEN];
+
+    let header = [
+        (0, 2),
+        (4, 1),
+        (8, 60),
+        (12, 1),
+        (16, SESSION_INFO.len() as i32 + 1),
+        (20, session_info_offset as i32),
+        (24, VARS.len() as i32),
+        (28, var_header_offset as i32),
+        (32, 1),
+        (36, RECORD_LEN as i32),
+        (48, RECORD_COUNT as i32),
+        (52, records_offset as i32),
+    ];
+    for (offset, value) in header {
+        put_i32(&mut file, offset, value);
+    }
+    put_i32(&mut file, 112 + 28, RECORD_COUNT as i32);
+
+    for (i, (name, var_type, count, offset)) in VARS.iter().enumerate() {
+        let at = var_header_offset + i * 144;
+        put_i32(&mut file, at, *var_type as i32);
+        put_i32(&mut file, at + 4, *offset as i32);
+        put_i32(&mut file, at + 8, *count as i32);
+        file[at + 16..at + 16 + name.len()].copy_from_slice(name.as_bytes());
+    }
+    file[session_info_offset..session_info_offset + SESSION_INFO.len()].copy_from_slice(SESSION_INFO.as_bytes());
+
+    for record in 0..RECORD_COUNT {
+        let at = records_offset + record * RECORD_LEN;
+        let r = record as f32;
+        file[at..at + 8].copy_from_slice(&(10.0 + record as f64 / 60.0).to_le_bytes());
+        file[at + 8..at + 12].copy_from_slice(&(50.0 + r).to_le_bytes());
+        put_i32(&mut file, at + 12, record as i32 + 1);
+        file[at + 16..at + 20].copy_from_slice(&(0x4u32 << record).to_le_bytes());
+        file[at + 20] = (record == 1) as u8;
+        file[at + 21] = record as u8;
+        for car in 0..3 {
+            file[at + 24 + car * 4..at + 28 + car * 4].copy_from_slice(&(0.25 * car as f32 + 0.125 * r).to_le_bytes());
+        }
+    }
+    file
+}
+
+#[test]
+fn decodes_records_from_telemetry_file() {
+    let path = std::env::temp_dir().join(format!("irsdk_ibt_{}.ibt", std::process::id()));
+    std::fs::write(&path, ibt_file()).unwrap();
+    let mut ibt = IBT::new();
+    ibt.open(path.to_str().unwrap()).unwrap();
+
+    assert_eq!(ibt.record_count(), RECORD_COUNT as i32);
+    assert_eq!(ibt.var_headers_names().len(), VARS.len());
+    assert_eq!(ibt.get(0, "SessionTime"), Some(VarValue::Double(10.0)));
+    assert_eq!(ibt.get(2, "Speed"), Some(VarValue::Float(52.0)));
+    assert_eq!(ibt.get(1, "Gear"), Some(VarValue::Int(2)));
+    assert_eq!(ibt.get(2, "SessionFlags"), Some(VarValue::BitField(0x10)));
+    assert_eq!(ibt.get(1, "OnPitRoad"), Some(VarValue::Bool(true)));
+    assert_eq!(ibt.get(2, "DCDriversSoFar"), Some(VarValue::Char(2)));
+    assert_eq!(ibt.get(1, "CarIdxLapDistPct"), Some(VarValue::FloatArray(vec![0.125, 0.375, 0.625])));
+    assert_eq!(ibt.get_as::<f32>(2, "Speed").unwrap(), 52.0);
+
+    assert_eq!(ibt.get(3, "Speed"), None);
+    assert_eq!(ibt.get(-1, "Speed"), None);
+    assert!(ibt.get_as::<f32>(3, "Speed").is_err());
+    assert_eq!(ibt.get(0, "RPM"), None);
+
+    let weekend_info = ibt.session_info().unwrap().weekend_info.as_ref().unwrap();
+    assert_eq!(weekend_info.track_name.as_deref(), Some("spa 2024 gp"));
+    assert_eq!(weekend_info.track_id, Some(524));
+    std::fs::remove_file(&path).unwrap();
+}
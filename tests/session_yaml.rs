@@ -0,0 +1,177 @@
+use std::fs;
+use std::path::Path;
+use irsdk::sanitize::sanitize_yaml;
+use irsdk::{ParsedSessionInfo, SessionDocument, SessionQuery, SetupNode, Unit, YamlFix};
+
+const FIXTURES: &str = "tests/fixtures/session_info";
+
+fn fixture(name: &str) -> Vec<u8> {
+    fs::read(Path::new(FIXTURES).join(name)).unwrap()
+}
+
+fn query(value: &serde_yaml::Value, query: &str) -> Option<String> {
+    SessionQuery::parse(query).unwrap().eval(value).and_then(irsdk::query::scalar_to_string)
+}
+
+fn quoted(fixes: &[YamlFix]) -> Vec<(usize, &str)> {
+    fixes
+        .iter()
+        .filter_map(|fix| match fix {
+            YamlFix::QuotedValue { line, key } => Some((*line, key.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn every_fixture_parses() {
+    for entry in fs::read_dir(FIXTURES).unwrap() {
+        let path = entry.unwrap().path();
+        let mut raw = fs::read(&path).unwrap();
+        raw.resize(raw.len() + 64, 0);
+        let sanitized = sanitize_yaml(&raw);
+        assert!(sanitized.fixes.contains(&YamlFix::TrailingNuls), "{}", path.display());
+        if let Err(e) = serde_yaml::from_str::<SessionDocument>(&sanitized.yaml) {
+            panic!("{}: {}\n{}", path.display(), e, sanitized.yaml);
+        }
+    }
+}
+
+#[test]
+fn leaves_clean_session_info_alone() {
+    let raw = fixture("clean.yaml");
+    let sanitized = sanitize_yaml(&raw);
+    assert_eq!(sanitized.fixes, []);
+    assert_eq!(sanitized.yaml.as_bytes(), &raw[..]);
+}
+
+#[test]
+fn quotes_names_with_special_chars() {
+    let sanitized = sanitize_yaml(&fixture("special_char_names.yaml"));
+    assert_eq!(
+        quoted(&sanitized.fixes),
+        [(12, "TeamName"), (14, "UserName"), (15, "TeamName"), (16, "CarDesignStr"), (21, "TeamName")]
+    );
+    let value: serde_yaml::Value = serde_yaml::from_str(&sanitized.yaml).unwrap();
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{1}UserName:").as_deref(), Some("Jean \"JEV\" Doe"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{1}AbbrevName:").as_deref(), Some("Doe, J"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{1}TeamName:").as_deref(), Some("#1 Racing"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{2}UserName:").as_deref(), Some("1e3"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{2}TeamName:").as_deref(), Some("[Sim] Racing & Co"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{2}CarDesignStr:").as_deref(), Some(",ff0000,000000"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{3}UserName:").as_deref(), Some("O'Neil *Racer*"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{3}TeamName:").as_deref(), Some("Team: Alpha"));
+}
+
+#[test]
+fn quotes_setup_names_with_colons() {
+    let sanitized = sanitize_yaml(&fixture("setup_name_colon.yaml"));
+    assert_eq!(quoted(&sanitized.fixes), [(4, "DriverSetupName")]);
+    let document: SessionDocument = serde_yaml::from_str(&sanitized.yaml).unwrap();
+    let driver_info = document.driver_info.unwrap();
+    assert_eq!(driver_info.driver_setup_name.as_deref(), Some("baseline: wet v2.sto"));
+    assert_eq!(document.car_setup.unwrap().update_count, Some(3));
+}
+
+#[test]
+fn builds_typed_car_setup() {
+    let parsed = ParsedSessionInfo::parse(1, &fixture("setup_name_colon.yaml")).unwrap();
+    let setup = parsed.car_setup().unwrap();
+    assert_eq!(setup.update_count, Some(3));
+    let aero = setup.section("TiresAero").and_then(|section| section.section("AeroBalanceCalc")).unwrap();
+    let ride_height = aero.value("FrontRhAtSpeed").unwrap();
+    assert_eq!(ride_height.raw, "1.850\"");
+    assert_eq!(ride_height.unit(), Some(&Unit::Inches));
+    assert!((ride_height.to(Unit::Millimeters).unwrap() - 46.99).abs() < 1e-9);
+    assert_eq!(aero.value("RearWingAngle").and_then(|angle| angle.value()), Some(12.0));
+}
+
+#[test]
+fn replaces_tabs_and_control_chars() {
+    let sanitized = sanitize_yaml(&fixture("tabs.yaml"));
+    assert_eq!(sanitized.fixes, [YamlFix::Tabs { count: 2 }]);
+    let value: serde_yaml::Value = serde_yaml::from_str(&sanitized.yaml).unwrap();
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{0}TeamName:").as_deref(), Some("Red Bull Junior"));
+
+    let sanitized = sanitize_yaml(&fixture("crlf.yaml"));
+    assert_eq!(sanitized.fixes, [YamlFix::ControlChars { count: 6 }]);
+    assert!(!sanitized.yaml.contains('\r'));
+}
+
+#[test]
+fn decodes_windows_1252_names() {
+    let sanitized = sanitize_yaml(&fixture("cp1252_names.yaml"));
+    let value: serde_yaml::Value = serde_yaml::from_str(&sanitized.yaml).unwrap();
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{1}UserName:").as_deref(), Some("José Müller"));
+    assert_eq!(query(&value, "DriverInfo:Drivers:CarIdx:{1}TeamName:").as_deref(), Some("Équipe Française"));
+}
+
+#[test]
+fn parses_full_race_capture() {
+    let parsed = ParsedSessionInfo::parse(7, &fixture("full_race.yaml")).unwrap();
+    assert_eq!(parsed.fixes, []);
+    let document = parsed.document.as_ref().unwrap();
+
+    let weekend_info = document.weekend_info.as_ref().unwrap();
+    assert_eq!(weekend_info.track_display_name.as_deref(), Some("Circuit de Spa-Francorchamps"));
+    assert_eq!(weekend_info.sub_session_id, Some(69912437));
+    let options = weekend_info.weekend_options.as_ref().unwrap();
+    assert_eq!(options.incident_limit.as_deref(), Some("17"));
+    assert_eq!(options.time_of_day.as_deref(), Some("1:30 pm"));
+
+    let session_info = document.session_info.as_ref().unwrap();
+    assert_eq!(session_info.current_session_num, Some(2));
+    let sessions = session_info.sessions.as_ref().unwrap();
+    assert_eq!(sessions.len(), 3);
+    assert!(sessions[0].results_positions.as_ref().is_none_or(Vec::is_empty));
+    let race = sessions[2].results_positions.as_ref().unwrap();
+    assert_eq!(race.iter().map(|p| p.car_idx.unwrap()).collect::<Vec<_>>(), [2, 1, 3, 4]);
+    assert_eq!(race[1].fastest_time, Some(137.8832));
+
+    let driver_info = document.driver_info.as_ref().unwrap();
+    let drivers = driver_info.drivers.as_ref().unwrap();
+    assert_eq!(drivers.len(), 5);
+    assert_eq!(drivers[4].user_name.as_deref(), Some("Tom O'Brien"));
+    assert_eq!(drivers[2].car_number.as_deref(), Some("17"));
+    assert_eq!(drivers[1].car_class_color, Some(0xffda59));
+
+    assert_eq!(document.qualify_results_info.as_ref().unwrap().results.as_ref().unwrap().len(), 4);
+    assert_eq!(document.camera_info.as_ref().unwrap().groups.as_ref().unwrap()[5].is_scenic, Some(true));
+    assert_eq!(document.radio_info.as_ref().unwrap().radios.as_ref().unwrap()[0].frequencies.as_ref().unwrap().len(), 6);
+    assert_eq!(document.split_time_info.as_ref().unwrap().sectors.as_ref().unwrap().len(), 6);
+
+    let setup = parsed.car_setup().unwrap();
+    assert_eq!(setup.update_count, Some(2));
+    let fuel = setup.find(&["Chassis", "Rear", "FuelLevel"]).and_then(SetupNode::as_value).unwrap();
+    assert_eq!(fuel.unit(), Some(&Unit::Liters));
+    assert_eq!(fuel.value(), Some(78.0));
+
+    let drivetrain = setup.find(&["Chassis", "Drivetrain"]).and_then(SetupNode::as_section).unwrap();
+    let text = |key| drivetrain.value(key).filter(|value| value.quantity.is_none()).map(|value| value.raw.as_str());
+    assert_eq!(text("FinalDriveRatio"), Some("17:1"));
+    assert_eq!(text("PitLimiterGear"), Some("2nd"));
+    assert_eq!(drivetrain.value("DiffPreload").and_then(|value| value.unit()), Some(&Unit::NewtonMeters));
+}
+
+#[test]
+fn repairs_a_defective_capture() {
+    let parsed = ParsedSessionInfo::parse(3, &fixture("night_race_capture.yaml")).unwrap();
+    assert_eq!(
+        parsed.fixes,
+        [
+            YamlFix::TrailingNuls,
+            YamlFix::Tabs { count: 2 },
+            YamlFix::QuotedValue { line: 37, key: "DriverSetupName".to_string() },
+            YamlFix::QuotedValue { line: 45, key: "TeamName".to_string() },
+            YamlFix::QuotedValue { line: 50, key: "TeamName".to_string() },
+            YamlFix::QuotedValue { line: 55, key: "TeamName".to_string() },
+        ]
+    );
+
+    let driver_info = parsed.document.as_ref().unwrap().driver_info.as_ref().unwrap();
+    assert_eq!(driver_info.driver_setup_name.as_deref(), Some("endurance: night v3.sto"));
+    let drivers = driver_info.drivers.as_ref().unwrap();
+    assert_eq!(drivers[1].user_name.as_deref(), Some("Lena Hartmann"));
+    let team_names: Vec<_> = drivers.iter().filter_map(|d| d.team_name.as_deref()).collect();
+    assert_eq!(team_names, ["Pace Car", "Black Falcon: Team Identica", "#77 Sim Endurance", "Scuderia Veloce #3"]);
+}